@@ -14,71 +14,122 @@ use glib::Object;
 use glib::Properties;
 use gtk::{gio, glib, CompositeTemplate};
 use std::cell::{Cell, RefCell};
-use std::path::PathBuf;
 use std::sync::OnceLock;
 
-use crate::{bookmarks_item::BookmarksItem, config::LOG_DOMAIN};
+use crate::{bookmarks_item::BookmarksItem, config::LOG_DOMAIN, util};
 
+// Where bookmarks were kept before using the GTK bookmarks file
 const CONFIG_DIR_NAME: &str = "pfs";
 const BOOKMARKS_FILE_NAME: &str = "bookmarks.xbel";
 
-async fn init_bookmarks() -> Option<(PathBuf, glib::BookmarkFile)> {
-    let mut config_dir = glib::user_config_dir();
-    config_dir.push(CONFIG_DIR_NAME);
-    let config_dir_file = gio::File::for_path(&config_dir);
-
-    if let Err(error) = config_dir_file
-        .make_directory_future(glib::Priority::DEFAULT)
-        .await
-    {
-        if !error.matches(gio::IOErrorEnum::Exists) {
-            glib::g_critical!(
-                LOG_DOMAIN,
-                "Failed to make configuration directory: {error}"
-            );
-            return None;
-        }
-    }
+// The GTK bookmarks file so bookmarks are shared with other apps
+pub(crate) fn gtk_bookmarks_file() -> gio::File {
+    let mut path = glib::user_config_dir();
+    path.push("gtk-3.0");
+    path.push("bookmarks");
+    gio::File::for_path(path)
+}
 
-    let Some(bookmarks_file_path) = config_dir_file.child(BOOKMARKS_FILE_NAME).path() else {
-        glib::g_critical!(LOG_DOMAIN, "Failed to get path to bookmarks file");
+// Bookmark lines are of the form `URI [label]`
+pub(crate) fn parse_bookmark(line: &str) -> Option<(&str, Option<&str>)> {
+    let line = line.trim();
+    if line.is_empty() {
         return None;
-    };
-    let mut bookmarks_file = glib::BookmarkFile::new();
+    }
+
+    match line.split_once(' ') {
+        Some((uri, label)) => Some((uri, Some(label.trim()).filter(|l| !l.is_empty()))),
+        None => Some((line, None)),
+    }
+}
 
-    if let Err(error) = bookmarks_file.load_from_file(&bookmarks_file_path) {
-        if error.matches(glib::FileError::Noent) {
-            if let Err(error) = bookmarks_file.to_file(&bookmarks_file_path) {
-                glib::g_critical!(LOG_DOMAIN, "Failed to initialize bookmarks file: {error}");
-                return None;
+fn has_bookmark(lines: &[String], uri: &str) -> bool {
+    lines
+        .iter()
+        .any(|line| parse_bookmark(line).is_some_and(|(u, _)| u == uri))
+}
+
+pub(crate) async fn load_bookmarks_file(file: &gio::File) -> String {
+    match file.load_contents_future().await {
+        Ok((contents, _)) => String::from_utf8_lossy(&contents).to_string(),
+        Err(error) => {
+            if !error.matches(gio::IOErrorEnum::NotFound) {
+                glib::g_warning!(LOG_DOMAIN, "Failed to load bookmarks: {error}");
             }
-        } else {
-            glib::g_critical!(LOG_DOMAIN, "Failed to load bookmarks file: {error}");
-            return None;
+            String::new()
         }
     }
-
-    Some((bookmarks_file_path, bookmarks_file))
 }
 
-fn save_bookmarks(bookmarks_file_path: &PathBuf, bookmarks_file: &glib::BookmarkFile) {
-    if let Err(error) = bookmarks_file.to_file(bookmarks_file_path) {
-        glib::g_critical!(LOG_DOMAIN, "Failed to save bookmarks file: {error}");
+// Rewrite the GTK bookmarks file, `update` gets the current lines
+pub(crate) async fn update_bookmarks<F: FnOnce(&mut Vec<String>)>(
+    update: F,
+) -> Result<(), glib::Error> {
+    let file = gtk_bookmarks_file();
+    let contents = load_bookmarks_file(&file).await;
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+
+    update(&mut lines);
+
+    if let Some(parent) = file.parent() {
+        if let Err(error) = parent.make_directory_future(glib::Priority::DEFAULT).await {
+            if !error.matches(gio::IOErrorEnum::Exists) {
+                return Err(error);
+            }
+        }
     }
+
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    file.replace_contents_future(
+        contents.into_bytes(),
+        None,
+        false,
+        gio::FileCreateFlags::NONE,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|(_, error)| error)
 }
 
-fn create_widget(object: &Object) -> gtk::Widget {
-    let info = object.downcast_ref::<gio::FileInfo>().unwrap();
+// Move bookmarks from pfs' own bookmarks file over to the GTK one
+async fn migrate_bookmarks() {
+    let mut path = glib::user_config_dir();
+    path.push(CONFIG_DIR_NAME);
+    path.push(BOOKMARKS_FILE_NAME);
+
+    let mut bookmarks_file = glib::BookmarkFile::new();
+    if let Err(error) = bookmarks_file.load_from_file(&path) {
+        if !error.matches(glib::FileError::Noent) {
+            glib::g_warning!(LOG_DOMAIN, "Failed to load old bookmarks: {error}");
+        }
+        return;
+    }
 
-    let binding = info.attribute_object("standard::file").unwrap();
-    let file = binding.downcast_ref::<gio::File>().unwrap();
+    let uris: Vec<String> = bookmarks_file
+        .uris()
+        .iter()
+        .map(|uri| uri.to_string())
+        .collect();
+    glib::g_debug!(LOG_DOMAIN, "Migrating {} bookmarks", uris.len());
+
+    let res = update_bookmarks(move |lines| {
+        for uri in uris {
+            if !has_bookmark(lines, &uri) {
+                lines.push(uri);
+            }
+        }
+    })
+    .await;
+    if let Err(error) = res {
+        glib::g_warning!(LOG_DOMAIN, "Failed to migrate bookmarks: {error}");
+        return;
+    }
 
-    let item = Object::builder::<BookmarksItem>()
-        .property("place", info.display_name())
-        .property("gicon", info.icon())
-        .property("uri", file.uri())
-        .build();
-    item.into()
+    let file = gio::File::for_path(&path);
+    if let Err(error) = file.delete_future(glib::Priority::DEFAULT).await {
+        glib::g_warning!(LOG_DOMAIN, "Failed to remove old bookmarks: {error}");
+    }
 }
 
 mod imp {
@@ -97,7 +148,12 @@ mod imp {
         #[property(get = Self::get_available, explicit_notify)]
         pub available: Cell<bool>,
 
-        pub bookmarks_file: RefCell<Option<(PathBuf, glib::BookmarkFile)>>,
+        // Notified whenever the bookmarks got (re)loaded
+        #[property(get, explicit_notify)]
+        pub n_bookmarks: Cell<u32>,
+
+        pub uris: RefCell<Vec<String>>,
+        pub monitor: RefCell<Option<gio::FileMonitor>>,
     }
 
     #[glib::object_subclass]
@@ -125,13 +181,8 @@ mod imp {
                 #[weak(rename_to = this)]
                 self,
                 async move {
-                    let (bookmarks_file_path, bookmarks_file) = match init_bookmarks().await {
-                        Some((bookmarks_file_path, bookmarks_file)) => {
-                            (bookmarks_file_path, bookmarks_file)
-                        }
-                        None => return,
-                    };
-                    this.on_init_bookmarks_ready(bookmarks_file_path, bookmarks_file);
+                    migrate_bookmarks().await;
+                    this.obj().setup_bookmarks();
                 }
             ));
         }
@@ -153,44 +204,6 @@ mod imp {
         fn get_available(&self) -> bool {
             self.available.get()
         }
-
-        fn on_items_changed(&self, model: gio::ListModel) {
-            let n_items = model.n_items();
-            let page_name = if n_items > 0 {
-                "flow_box"
-            } else {
-                "placeholder"
-            };
-            self.stack.set_visible_child_name(page_name);
-        }
-
-        fn on_init_bookmarks_ready(
-            &self,
-            bookmarks_file_path: PathBuf,
-            bookmarks_file: glib::BookmarkFile,
-        ) {
-            let model = gtk::BookmarkList::new(
-                Some(&bookmarks_file_path),
-                Some("standard::display-name,standard::icon"),
-            );
-            self.flow_box.bind_model(Some(&model), create_widget);
-
-            *self.bookmarks_file.borrow_mut() = Some((bookmarks_file_path, bookmarks_file));
-            self.available.set(true);
-            self.obj().notify_available();
-
-            model.connect_closure(
-                "items-changed",
-                false,
-                glib::closure_local!(
-                    #[weak(rename_to = this)]
-                    self,
-                    move |model: gio::ListModel, _: u32, _: u32, _: u32| this
-                        .on_items_changed(model)
-                ),
-            );
-            self.on_items_changed(model.upcast::<gio::ListModel>());
-        }
     }
 }
 
@@ -222,28 +235,123 @@ impl BookmarksBox {
         self.emit_by_name::<()>("new-uri", &[&uri]);
     }
 
+    fn setup_bookmarks(&self) {
+        // Pick up changes by other apps too
+        let file = gtk_bookmarks_file();
+        match file.monitor_file(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>) {
+            Ok(monitor) => {
+                monitor.connect_changed(glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_, _, _, event| {
+                        if event == gio::FileMonitorEvent::ChangesDoneHint
+                            || event == gio::FileMonitorEvent::Deleted
+                        {
+                            this.reload_bookmarks();
+                        }
+                    }
+                ));
+                *self.imp().monitor.borrow_mut() = Some(monitor);
+            }
+            Err(error) => glib::g_warning!(LOG_DOMAIN, "Can't monitor bookmarks: {error}"),
+        }
+
+        self.reload_bookmarks();
+    }
+
+    fn reload_bookmarks(&self) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let contents = load_bookmarks_file(&gtk_bookmarks_file()).await;
+                this.show_bookmarks(&contents);
+            }
+        ));
+    }
+
+    fn show_bookmarks(&self, contents: &str) {
+        let imp = self.imp();
+        let mut uris = Vec::new();
+
+        imp.flow_box.remove_all();
+        for (uri, label) in contents.lines().filter_map(parse_bookmark) {
+            let folder = gio::File::for_uri(uri);
+            let place = label
+                .map(str::to_string)
+                .unwrap_or_else(|| util::folder_to_name(folder.clone()));
+            let icon =
+                gio::ThemedIcon::new(util::folder_to_icon_name(folder)).upcast::<gio::Icon>();
+
+            let item = Object::builder::<BookmarksItem>()
+                .property("place", place)
+                .property("gicon", icon)
+                .property("uri", uri)
+                .build();
+            imp.flow_box.append(&item);
+            uris.push(uri.to_string());
+        }
+
+        let page_name = if uris.is_empty() {
+            "placeholder"
+        } else {
+            "flow_box"
+        };
+        imp.stack.set_visible_child_name(page_name);
+        self.set_uris(uris);
+
+        if !imp.available.replace(true) {
+            self.notify_available();
+        }
+    }
+
+    fn set_uris(&self, uris: Vec<String>) {
+        let imp = self.imp();
+
+        imp.n_bookmarks.set(uris.len() as u32);
+        *imp.uris.borrow_mut() = uris;
+        self.notify_n_bookmarks();
+    }
+
+    fn save_bookmarks<F: FnOnce(&mut Vec<String>) + 'static>(&self, update: F) {
+        glib::spawn_future_local(async move {
+            if let Err(error) = update_bookmarks(update).await {
+                glib::g_warning!(LOG_DOMAIN, "Failed to save bookmarks: {error}");
+            }
+        });
+    }
+
     pub fn add_bookmark(&self, uri: &str) {
-        let mut borrow = self.imp().bookmarks_file.borrow_mut();
-        let (bookmarks_file_path, bookmarks_file) = borrow.as_mut().unwrap();
+        if self.is_bookmark(uri) {
+            return;
+        }
 
         glib::g_debug!(LOG_DOMAIN, "Adding bookmark {uri:#?}");
-        bookmarks_file.add_application(uri, None, None);
-        save_bookmarks(bookmarks_file_path, bookmarks_file);
+        let mut uris = self.imp().uris.borrow().clone();
+        uris.push(uri.to_string());
+        self.set_uris(uris);
+
+        let uri = uri.to_string();
+        self.save_bookmarks(move |lines| {
+            if !has_bookmark(lines, &uri) {
+                lines.push(uri);
+            }
+        });
     }
 
     pub fn del_bookmark(&self, uri: &str) {
-        let mut borrow = self.imp().bookmarks_file.borrow_mut();
-        let (bookmarks_file_path, bookmarks_file) = borrow.as_mut().unwrap();
-
         glib::g_debug!(LOG_DOMAIN, "Deleting bookmark {uri:#?}");
-        bookmarks_file.remove_item(uri).unwrap();
-        save_bookmarks(bookmarks_file_path, bookmarks_file);
+        let mut uris = self.imp().uris.borrow().clone();
+        uris.retain(|u| u != uri);
+        self.set_uris(uris);
+
+        let uri = uri.to_string();
+        self.save_bookmarks(move |lines| {
+            lines.retain(|line| !parse_bookmark(line).is_some_and(|(u, _)| u == uri));
+        });
     }
 
     pub fn is_bookmark(&self, uri: &str) -> bool {
-        let borrow = self.imp().bookmarks_file.borrow();
-        let (_, bookmarks_file) = borrow.as_ref().unwrap();
-
-        bookmarks_file.has_item(uri)
+        self.imp().uris.borrow().iter().any(|u| u == uri)
     }
 }
@@ -16,6 +16,9 @@ use std::sync::OnceLock;
 
 use crate::config::LOG_DOMAIN;
 
+// How many recently visited folders to remember
+const MAX_RECENT: u32 = 10;
+
 mod imp {
     use super::*;
 
@@ -33,6 +36,18 @@ mod imp {
         #[property(get, set = Self::set_folder)]
         folder: RefCell<Option<gio::File>>,
 
+        // Whether there's a previous folder in the history
+        #[property(get, explicit_notify)]
+        can_go_back: Cell<bool>,
+
+        // Whether there's a next folder in the history
+        #[property(get, explicit_notify)]
+        can_go_forward: Cell<bool>,
+
+        // Recently visited folders, most recent first
+        #[property(get)]
+        recent: RefCell<Option<gio::ListStore>>,
+
         pub(super) is_updating: Cell<bool>,
         pub(super) position: Cell<usize>,
         pub(super) dirstack: RefCell<Vec<gio::File>>,
@@ -65,11 +80,38 @@ mod imp {
 
     impl DirStack {
         pub fn update_actions(&self, pos: usize, len: usize) {
+            let obj = self.obj();
+
             let enabled = len > 0 && pos < len - 1;
-            self.obj().action_set_enabled("dir.next", enabled);
+            obj.action_set_enabled("dir.next", enabled);
+            if self.can_go_forward.replace(enabled) != enabled {
+                obj.notify_can_go_forward();
+            }
 
             let enabled = len > 0 && pos > 0;
-            self.obj().action_set_enabled("dir.prev", enabled);
+            obj.action_set_enabled("dir.prev", enabled);
+            if self.can_go_back.replace(enabled) != enabled {
+                obj.notify_can_go_back();
+            }
+        }
+
+        pub(super) fn add_recent(&self, folder: &gio::File) {
+            let binding = self.recent.borrow();
+            let Some(recent) = binding.as_ref() else {
+                return;
+            };
+
+            if let Some(pos) = recent
+                .iter::<gio::File>()
+                .position(|file| file.is_ok_and(|file| file.equal(folder)))
+            {
+                recent.remove(pos as u32);
+            }
+            recent.insert(0, folder);
+
+            if recent.n_items() > MAX_RECENT {
+                recent.remove(MAX_RECENT);
+            }
         }
 
         fn set_folder(&self, folder: Option<gio::File>) {
@@ -92,6 +134,7 @@ mod imp {
 
             glib::g_debug!(LOG_DOMAIN, "Stacking {uri:#?} at {pos:#?}");
 
+            self.add_recent(&folder);
             *self.folder.borrow_mut() = Some(folder);
             self.update_actions(pos, stack.len());
         }
@@ -101,6 +144,8 @@ mod imp {
     impl ObjectImpl for DirStack {
         fn constructed(&self) {
             self.parent_constructed();
+
+            *self.recent.borrow_mut() = Some(gio::ListStore::new::<gio::File>());
         }
 
         fn signals() -> &'static [Signal] {
@@ -135,6 +180,18 @@ impl DirStack {
         Self::default()
     }
 
+    pub fn back(&self) {
+        if self.can_go_back() {
+            self.goto(-1);
+        }
+    }
+
+    pub fn forward(&self) {
+        if self.can_go_forward() {
+            self.goto(1);
+        }
+    }
+
     pub fn goto(&self, skip: i64) {
         let mut pos = self.imp().position.get() as i64;
         let stack = self.imp().dirstack.borrow_mut();
@@ -153,6 +210,7 @@ impl DirStack {
         let pos = pos as usize;
         self.imp().position.replace(pos);
         let uri = stack[pos].uri();
+        self.imp().add_recent(&stack[pos]);
         self.imp().update_actions(pos, len as usize);
 
         self.imp().is_updating.replace(true);
@@ -10,13 +10,20 @@ use adw::prelude::*;
 use adw::subclass::prelude::*;
 use glib::subclass::Signal;
 use glib::Properties;
-use gtk::{gio, glib, CompositeTemplate};
+use gtk::{gdk, gio, glib, CompositeTemplate};
 use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::rc::Rc;
 use std::sync::OnceLock;
 
-use crate::{config::LOG_DOMAIN, file_selector::SortMode, grid_item::GridItem, util};
+use crate::{
+    config::LOG_DOMAIN,
+    file_selector::{ActivateAction, FileSelector, SortMode},
+    grid_item::GridItem,
+    util,
+};
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
 #[enum_type(name = "PfsDirViewThumbnailMode")]
@@ -24,6 +31,35 @@ pub enum ThumbnailMode {
     #[default]
     Never,
     Local,
+    // Also thumbnail files on non-local (e.g. mtp:// or smb://) mounts
+    Remote,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
+#[enum_type(name = "PfsDirViewViewMode")]
+pub enum ViewMode {
+    #[default]
+    Grid, // icons in a grid
+    List, // compact rows with name, size and modification time
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
+#[enum_type(name = "PfsDirViewItemDetails")]
+pub enum ItemDetails {
+    #[default]
+    All, // size and modification time
+    Size,
+    Modified,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
+#[enum_type(name = "PfsDirViewDirectoriesFirstMode")]
+pub enum DirectoriesFirstMode {
+    #[default]
+    Always, // folders before files regardless of the sort direction
+    Never, // folders and files are sorted together
+    // folders before files, after them when the sort is reversed
+    FollowSort,
 }
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
@@ -31,18 +67,195 @@ pub enum ThumbnailMode {
 pub enum DisplayMode {
     #[default]
     Content, // folder content is displayed
-    Search,  // search results are displayed
-    Loading, // folder content is loading
+    Search,   // search results are displayed
+    Loading,  // folder content is loading
+    NoFolder, // no folder is set
 }
 
-// Used to create thumbnails, optional, so any code using it should be fail-safe.
+// Used to create thumbnails by default, optional, so any code using it
+// should be fail-safe.
 const THUMBNAILER_NAME: &str = "mobi.phosh.Thumbnailer";
 const THUMBNAILER_PATH: &str = "/mobi/phosh/Thumbnailer";
 const THUMBNAILER_IFACE: &str = "mobi.phosh.Thumbnailer";
 
-// We will store the files without thumbnail in a map.
+// We will store the visible files without thumbnail in a map, files
+// scrolled out of view get dropped again on unbind.
 // Once we get no more files for these seconds, then we will send them for thumbnailing.
 const THUMBNAILS_DEBOUNCE_SECS: u32 = 1;
+// Show the letter index for folders with more items than this
+const LETTER_INDEX_MIN_ITEMS: u32 = 100;
+
+// Type-ahead forgets the typed prefix after this many milliseconds
+const TYPE_AHEAD_TIMEOUT_MS: u64 = 1000;
+
+// Only show the loading page for folders that take longer than this
+// many milliseconds so fast (e.g. local) folders don't flicker
+const LOADING_GRACE_MS: u64 = 150;
+
+// Swipes starting this close to the edges are left to e.g. navigation views
+const SWIPE_EDGE_PX: f64 = 24.0;
+// Horizontal velocity (in px/s) a swipe needs to go to the parent folder
+const SWIPE_BACK_MIN_VELOCITY: f64 = 500.0;
+
+// Forget about our own removals this many milliseconds after the
+// operation finished, the folder monitor has caught up by then
+const OWN_REMOVAL_GRACE_MS: u64 = 2000;
+
+// The icon sizes offered in the grid view
+pub(crate) const ICON_SIZES: &[u32] = &[32, 48, 64, 96, 128, 256];
+
+// Send the batch anyway when binds keep coming in for this long (in µs)…
+const THUMBNAILS_MAX_DELAY_USECS: i64 = 3 * 1_000_000;
+// …or once it got that large
+const THUMBNAILS_BATCH_SIZE: usize = 64;
+
+// Split larger batches into D-Bus calls with at most that many files…
+const THUMBNAILS_MAX_FILES_PER_CALL: usize = 200;
+// …and wait this many milliseconds between them
+const THUMBNAILS_CALL_DELAY_MS: u64 = 100;
+
+// Thumbnail requests collected since the last batch got sent
+#[derive(Debug, Default)]
+struct ThumbnailBatch {
+    // Monotonic time (in µs) of the first request in this batch
+    started: Option<i64>,
+    n_requests: usize,
+}
+
+impl ThumbnailBatch {
+    // Adds a request, returns `true` if the batch should be sent right away
+    fn add(&mut self, now: i64) -> bool {
+        let started = *self.started.get_or_insert(now);
+        self.n_requests += 1;
+
+        self.n_requests >= THUMBNAILS_BATCH_SIZE || now - started >= THUMBNAILS_MAX_DELAY_USECS
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// How many operations can be undone
+const UNDO_STACK_DEPTH: usize = 10;
+
+// A file operation that can be undone
+#[derive(Debug, Clone)]
+pub(crate) enum UndoOp {
+    // The file got moved to the trash
+    Trash(gio::File),
+    // Files got moved, as pairs of original and new location
+    Move(Vec<(gio::File, gio::File)>),
+    // The file got renamed from `old_name`
+    Rename { file: gio::File, old_name: String },
+}
+
+#[derive(Debug)]
+struct UndoEntry {
+    id: u64,
+    op: UndoOp,
+    toast: adw::Toast,
+}
+
+// A sort function set by the embedder, replaces the built-in sort modes
+#[derive(Clone)]
+struct CustomSortFunc(Rc<dyn Fn(&gio::FileInfo, &gio::FileInfo) -> Ordering>);
+
+impl std::fmt::Debug for CustomSortFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomSortFunc")
+    }
+}
+
+// A filter function set by the embedder, applied on top of the built-in filters
+#[derive(Clone)]
+struct CustomFilterFunc(Rc<dyn Fn(&gio::FileInfo) -> bool>);
+
+impl std::fmt::Debug for CustomFilterFunc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CustomFilterFunc")
+    }
+}
+
+// The item and child widget of a list item or column view cell
+fn list_item_parts(object: &glib::Object) -> (Option<glib::Object>, Option<gtk::Widget>) {
+    if let Some(cell) = object.downcast_ref::<gtk::ColumnViewCell>() {
+        return (cell.item(), cell.child());
+    }
+
+    let list_item = object.downcast_ref::<gtk::ListItem>().unwrap();
+    (list_item.item(), list_item.child())
+}
+
+// Keys derived from a display name for searching and sorting. Sorting
+// and filtering run for every item on each change so these are computed
+// once per name rather than on each comparison.
+#[derive(Debug)]
+struct NameKeys {
+    lowercase: String,
+    collation: glib::FilenameCollationKey,
+}
+
+impl NameKeys {
+    fn new(display_name: &str) -> Self {
+        Self {
+            lowercase: display_name.to_lowercase(),
+            collation: glib::FilenameCollationKey::from(display_name),
+        }
+    }
+}
+
+// Deterministic order for items that are equal by the sort mode so they
+// don't shuffle around when the folder reloads
+fn tie_break(
+    info1: &gio::FileInfo,
+    keys1: &NameKeys,
+    info2: &gio::FileInfo,
+    keys2: &NameKeys,
+) -> Ordering {
+    keys1
+        .collation
+        .cmp(&keys2.collation)
+        .then_with(|| info1.name().cmp(&info2.name()))
+}
+
+// The clipboard target GNOME Files uses to tell copied from cut files
+const GNOME_COPIED_FILES: &str = "x-special/gnome-copied-files";
+
+// Clipboard content for `GNOME_COPIED_FILES`: the operation followed by
+// one URI per line
+fn copied_files_content(files: &[gio::File], cut: bool) -> String {
+    let operation = if cut { "cut" } else { "copy" };
+
+    std::iter::once(operation.to_string())
+        .chain(files.iter().map(|file| file.uri().to_string()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The files and whether they were cut from `GNOME_COPIED_FILES` content
+fn parse_copied_files(content: &str) -> Option<(Vec<gio::File>, bool)> {
+    let mut lines = content.lines();
+    let cut = match lines.next()? {
+        "cut" => true,
+        "copy" => false,
+        _ => return None,
+    };
+
+    let files = lines
+        .filter(|line| !line.is_empty())
+        .map(gio::File::for_uri)
+        .collect();
+    Some((files, cut))
+}
+
+// The URI of the file currently shown by a grid item
+fn grid_item_uri(item: &GridItem) -> Option<String> {
+    let info = item.fileinfo()?;
+    let object = info.attribute_object("standard::file")?;
+    let file = object.downcast_ref::<gio::File>()?;
+    Some(file.uri().to_string())
+}
 
 mod imp {
     use super::*;
@@ -51,9 +264,24 @@ mod imp {
     #[template(resource = "/mobi/phosh/FileSelector/dir-view.ui")]
     #[properties(wrapper_type = super::DirView)]
     pub struct DirView {
+        #[template_child]
+        pub folder_stack: TemplateChild<gtk::Stack>,
+
         #[template_child]
         pub grid_view: TemplateChild<gtk::GridView>,
 
+        #[template_child]
+        pub column_view: TemplateChild<gtk::ColumnView>,
+
+        #[template_child]
+        pub name_column: TemplateChild<gtk::ColumnViewColumn>,
+
+        #[template_child]
+        pub size_column: TemplateChild<gtk::ColumnViewColumn>,
+
+        #[template_child]
+        pub modified_column: TemplateChild<gtk::ColumnViewColumn>,
+
         #[template_child]
         pub view_stack: TemplateChild<gtk::Stack>,
 
@@ -72,14 +300,57 @@ mod imp {
         #[template_child]
         pub item_factory: TemplateChild<gtk::SignalListItemFactory>,
 
-        // The folder to display
-        #[property(get, set = Self::set_folder, explicit_notify)]
+        #[template_child]
+        pub transfer_box: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub transfer_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub transfer_size_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub progress_bar: TemplateChild<gtk::ProgressBar>,
+
+        #[template_child]
+        pub refresh_spinner: TemplateChild<adw::Spinner>,
+
+        #[template_child]
+        pub letter_index: TemplateChild<gtk::Box>,
+
+        // The folder to display, `None` clears the view
+        #[property(get, set = Self::set_folder, nullable, explicit_notify)]
         folder: RefCell<Option<gio::File>>,
 
         // `true` if there's a selected item
         #[property(get, explicit_notify)]
         pub(super) has_selection: Cell<bool>,
 
+        // The number of selected items
+        #[property(get, explicit_notify)]
+        pub(super) selection_count: Cell<u32>,
+
+        // The number of items shown after filtering
+        #[property(get, explicit_notify)]
+        pub(super) n_items: Cell<u32>,
+
+        // The shown items along with their selection. This is a live model
+        // that follows folder changes, filtering and sorting.
+        #[property(get = Self::get_model)]
+        pub(super) model: PhantomData<gtk::SelectionModel>,
+
+        // `true` if the folder has items but all of them got filtered out
+        #[property(get, explicit_notify)]
+        pub(super) filtered_out: Cell<bool>,
+
+        // `true` if the folder only looks empty as hidden files aren't shown
+        #[property(get, explicit_notify)]
+        pub(super) has_hidden_files: Cell<bool>,
+
+        // Whether the clipboard has files that can be pasted into the folder
+        #[property(get, explicit_notify)]
+        pub(super) can_paste: Cell<bool>,
+
         #[property(get, builder(DisplayMode::default()))]
         pub display_mode: Cell<DisplayMode>,
 
@@ -87,9 +358,41 @@ mod imp {
         #[property(get, set = Self::set_search_term, explicit_notify)]
         pub(super) search_term: RefCell<Option<String>>,
 
+        // Shell style wildcard pattern (e.g. `*.log`) file names must match
+        #[property(get, set = Self::set_glob_filter, nullable, explicit_notify)]
+        pub(super) glob_filter: RefCell<Option<String>>,
+
         // Icon size of the items in the grid view
         #[property(get, set)]
-        icon_size: Cell<u32>,
+        pub(super) icon_size: Cell<u32>,
+
+        // The icon size when a pinch gesture started
+        pub(super) zoom_start_size: Cell<u32>,
+
+        // Whether to show an A-Z strip to jump through large folders
+        #[property(get, set, construct, default = true)]
+        pub(super) show_letter_index: Cell<bool>,
+
+        // Whether typing jumps to the first matching item. Off when
+        // typing goes to a search entry instead.
+        #[property(get, set, construct, default = true)]
+        pub(super) type_ahead: Cell<bool>,
+
+        // Whether to show the files as grid or as list
+        #[property(get, set = Self::set_view_mode, explicit_notify, builder(ViewMode::default()))]
+        pub(super) view_mode: Cell<ViewMode>,
+
+        // Whether activating a file accepts or just selects it
+        #[property(get, set, builder(ActivateAction::default()))]
+        pub(super) file_activation: Cell<ActivateAction>,
+
+        // Whether to show details below the file names in the grid
+        #[property(get, set)]
+        pub(super) show_details: Cell<bool>,
+
+        // Which details to show when `show-details` is set
+        #[property(get, set, builder(ItemDetails::default()))]
+        pub(super) item_details: Cell<ItemDetails>,
 
         // What to sort for
         #[property(get, set = Self::set_sort_mode, builder(SortMode::default()))]
@@ -100,10 +403,11 @@ mod imp {
         pub(super) reversed: Cell<bool>,
 
         // Whether to sort directories before files
-        #[property(get, set)]
-        pub(super) directories_first: Cell<bool>,
+        #[property(get, set = Self::set_directories_first_mode, explicit_notify,
+                   builder(DirectoriesFirstMode::default()))]
+        pub(super) directories_first_mode: Cell<DirectoriesFirstMode>,
 
-        // Whether to show hidden files
+        // Whether to show hidden and backup files
         #[property(get, set, set = Self::set_show_hidden, explicit_notify)]
         pub(super) show_hidden: Cell<bool>,
 
@@ -111,6 +415,11 @@ mod imp {
         #[property(get, set = Self::set_directories_only, explicit_notify)]
         pub(super) directories_only: Cell<bool>,
 
+        // Whether tapping a folder selects it rather than entering it when
+        // selecting directories. Activating the folder still enters it.
+        #[property(get, set)]
+        pub(super) select_directories: Cell<bool>,
+
         // The current filter type filter
         #[property(get, set = Self::set_type_filter, construct, nullable, explicit_notify)]
         pub(super) type_filter: RefCell<Option<gtk::FileFilter>>,
@@ -119,16 +428,103 @@ mod imp {
         #[property(get, set = Self::set_type_filter, nullable, explicit_notify)]
         pub(super) real_filter: RefCell<Option<gtk::FileFilter>>,
 
+        // Only show files modified at or after this time
+        #[property(get, set = Self::set_min_modified, nullable, explicit_notify)]
+        pub(super) min_modified: RefCell<Option<glib::DateTime>>,
+
+        // Only show files modified at or before this time
+        #[property(get, set = Self::set_max_modified, nullable, explicit_notify)]
+        pub(super) max_modified: RefCell<Option<glib::DateTime>>,
+
+        // Only show files at least this large (in bytes), 0 means unbounded
+        #[property(get, set = Self::set_min_size, explicit_notify)]
+        pub(super) min_size: Cell<u64>,
+
+        // Only show files at most this large (in bytes), 0 means unbounded
+        #[property(get, set = Self::set_max_size, explicit_notify)]
+        pub(super) max_size: Cell<u64>,
+
         // Whether to show thumbnails
         #[property(get, set, builder(ThumbnailMode::default()))]
         pub thumbnail_mode: RefCell<ThumbnailMode>,
 
+        // How often to retry thumbnails that failed transiently. The delay
+        // doubles with each attempt so keep this small.
+        #[property(get, set, construct, default = 2, maximum = 5)]
+        pub thumbnail_retries: Cell<u32>,
+
+        // The D-Bus service used to create thumbnails, allows to use
+        // a thumbnailer other than phosh's
+        #[property(get, set, construct_only, default = THUMBNAILER_NAME)]
+        pub thumbnailer_name: RefCell<String>,
+        #[property(get, set, construct_only, default = THUMBNAILER_PATH)]
+        pub thumbnailer_path: RefCell<String>,
+        #[property(get, set, construct_only, default = THUMBNAILER_IFACE)]
+        pub thumbnailer_iface: RefCell<String>,
+
+        // Whether thumbnails can be requested. Only cleared when the
+        // thumbnailer can't be found or activated, not when it exits
+        #[property(get, explicit_notify)]
+        pub(super) thumbnailer_available: Cell<bool>,
+
         pub cancellable: RefCell<gio::Cancellable>,
+        // Cancels thumbnail requests for the current folder
+        thumbnails_cancellable: RefCell<gio::Cancellable>,
         pub debounce_id: RefCell<Option<glib::SourceId>>,
+        pub(super) thumbnail_batch: RefCell<ThumbnailBatch>,
         pub no_thumbnails: RefCell<HashMap<String, GridItem>>,
+        // URIs sent (or about to be sent) to the thumbnailer that it
+        // didn't report back on yet
+        thumbnails_in_flight: RefCell<HashSet<String>>,
+        // Number of failed attempts per URI
+        thumbnail_attempts: RefCell<HashMap<String, u32>>,
+
+        // Search and sort keys by display name
+        pub(super) name_keys: RefCell<HashMap<glib::GString, Rc<NameKeys>>>,
         pub thumbnailer_proxy: RefCell<Option<gio::DBusProxy>>,
 
         pub select_item_id: RefCell<Option<glib::SignalHandlerId>>,
+        // The selected file so we notice when it gets removed
+        pub(super) selected_file: RefCell<Option<gio::File>>,
+        // Files we trash, delete or rename ourselves
+        pub(super) own_removals: RefCell<Vec<gio::File>>,
+
+        // Overrides `sort_mode` when set
+        pub(super) custom_sort_func: RefCell<Option<CustomSortFunc>>,
+        // Additionally filters items when set
+        pub(super) custom_filter_func: RefCell<Option<CustomFilterFunc>>,
+
+        // Set while the column headers get updated from `sort-mode` and `reversed`
+        pub syncing_sort_headers: Cell<bool>,
+
+        // The prefix typed so far for type-ahead find
+        pub type_ahead_prefix: RefCell<String>,
+        pub type_ahead_timeout_id: RefCell<Option<glib::SourceId>>,
+
+        // Pending switch to the loading page
+        pub loading_grace_id: RefCell<Option<glib::SourceId>>,
+
+        // Move the focus into the folder once it loaded
+        pub focus_on_load: Cell<bool>,
+
+        // Where the current swipe started
+        pub swipe_start_x: Cell<Option<f64>>,
+        // Whether a finger is on the view
+        pub touching: Cell<bool>,
+
+        pub drop_target: RefCell<Option<gtk::DropTarget>>,
+        // Whether the current folder is writable
+        pub folder_writable: Cell<bool>,
+        // Cancel the running copy or move operations, the progress shown
+        // is the one of the most recent
+        pub transfer_cancellables: RefCell<Vec<gio::Cancellable>>,
+
+        // Operations that can still be undone, most recent last
+        pub(super) undo_stack: RefCell<Vec<UndoEntry>>,
+        pub(super) undo_serial: Cell<u64>,
+
+        // The glob filter as file filter so we can match against it
+        pub glob_file_filter: RefCell<Option<gtk::FileFilter>>,
     }
 
     #[glib::object_subclass]
@@ -150,6 +546,10 @@ mod imp {
     }
 
     impl DirView {
+        fn get_model(&self) -> gtk::SelectionModel {
+            self.single_selection.get().upcast()
+        }
+
         // r/o property
         pub(super) fn set_has_selection(&self, has_selection: bool) {
             if has_selection == self.has_selection.get() {
@@ -160,14 +560,27 @@ mod imp {
             self.obj().notify_has_selection();
         }
 
-        fn update_directory_selection(&self) {
+        pub(super) fn update_selection_count(&self) {
+            let count = self.single_selection.selection().size() as u32;
+
+            if count == self.selection_count.get() {
+                return;
+            }
+
+            self.selection_count.set(count);
+            self.obj().notify_selection_count();
+        }
+
+        pub(super) fn update_directory_selection(&self) {
             // In directory selection mode we have a selection whenever
-            // we're in a valid dir (e.g. not in recent:///
+            // we're in a valid dir (e.g. not in recent:///) or a subfolder
+            // is selected
             if !self.directories_only.get() {
                 return;
             }
 
-            let has_selection = util::is_valid_folder(self.folder.borrow().as_ref());
+            let has_selection = util::is_valid_folder(self.folder.borrow().as_ref())
+                || self.obj().selected_subfolder().is_some();
             self.set_has_selection(has_selection);
         }
 
@@ -175,24 +588,90 @@ mod imp {
             let obj = self.obj();
             let oldfolder = self.folder.borrow().clone();
 
-            if folder.is_none() {
+            let Some(folder) = folder else {
+                if oldfolder.is_none() {
+                    return;
+                }
+
+                glib::g_debug!(LOG_DOMAIN, "Clearing folder");
+                self.reset_thumbnails();
+                obj.reset_type_ahead();
+
+                *self.folder.borrow_mut() = None;
+                obj.notify_folder();
+                obj.emit_by_name::<()>("folder-changed", &[&String::new()]);
+
+                self.display_mode.replace(DisplayMode::NoFolder);
+                obj.notify_display_mode();
+
+                self.update_directory_selection();
+                obj.update_drop_target();
                 return;
-            }
+            };
 
-            let folder = folder.unwrap();
-            if oldfolder.is_some() && folder.equal(&oldfolder.unwrap()) {
+            if oldfolder.is_some_and(|oldfolder| folder.equal(&oldfolder)) {
+                // Virtual locations like recent:/// or trash:/// aren't
+                // reliably monitored so refresh them when entered again
+                if folder.path().is_none() {
+                    obj.reload();
+                }
                 return;
             }
 
             let uri = folder.uri();
             glib::g_debug!(LOG_DOMAIN, "Loading folder for {uri:#?}");
 
-            self.no_thumbnails.borrow_mut().clear();
+            self.reset_thumbnails();
+            obj.reset_type_ahead();
 
             *self.folder.borrow_mut() = Some(folder);
+            self.focus_on_load.set(true);
+            self.own_removals.borrow_mut().clear();
             obj.notify_folder();
+            obj.emit_by_name::<()>("folder-changed", &[&uri.to_string()]);
 
             self.update_directory_selection();
+            obj.update_drop_target();
+        }
+
+        fn set_view_mode(&self, view_mode: ViewMode) {
+            if self.view_mode.get() == view_mode {
+                return;
+            }
+            self.view_mode.set(view_mode);
+            self.update_view_mode();
+            self.obj().notify_view_mode();
+        }
+
+        // Only the visible view has a model so items don't get bound twice
+        pub(super) fn update_view_mode(&self) {
+            let model = self.single_selection.get();
+
+            match self.view_mode.get() {
+                ViewMode::Grid => {
+                    self.column_view.set_model(None::<&gtk::SelectionModel>);
+                    self.grid_view.set_model(Some(&model));
+                    self.folder_stack.set_visible_child_name("grid");
+                }
+                ViewMode::List => {
+                    self.grid_view.set_model(None::<&gtk::SelectionModel>);
+                    self.column_view.set_model(Some(&model));
+                    self.folder_stack.set_visible_child_name("list");
+                }
+            }
+        }
+
+        // Forget about pending thumbnails and cached name keys, e.g. when
+        // the folder changes
+        pub(super) fn reset_thumbnails(&self) {
+            self.thumbnails_cancellable
+                .replace(gio::Cancellable::new())
+                .cancel();
+            self.no_thumbnails.borrow_mut().clear();
+            self.thumbnails_in_flight.borrow_mut().clear();
+            self.thumbnail_batch.borrow_mut().reset();
+            self.thumbnail_attempts.borrow_mut().clear();
+            self.name_keys.borrow_mut().clear();
         }
 
         fn set_show_hidden(&self, show_hidden: bool) {
@@ -235,6 +714,15 @@ mod imp {
             self.obj().set_sorting(mode, reversed);
         }
 
+        fn set_directories_first_mode(&self, mode: DirectoriesFirstMode) {
+            if self.directories_first_mode.replace(mode) == mode {
+                return;
+            }
+
+            self.obj().resort();
+            self.obj().notify_directories_first_mode();
+        }
+
         fn set_directories_only(&self, directories_only: bool) {
             let obj = self.obj();
 
@@ -287,6 +775,82 @@ mod imp {
             obj.notify_real_filter();
         }
 
+        fn set_min_modified(&self, min_modified: Option<glib::DateTime>) {
+            if *self.min_modified.borrow() == min_modified {
+                return;
+            }
+
+            *self.min_modified.borrow_mut() = min_modified;
+
+            let filter = self.filtered_list.filter().unwrap();
+            filter.emit_by_name::<()>("changed", &[&gtk::FilterChange::Different]);
+            self.obj().notify_min_modified();
+        }
+
+        fn set_max_modified(&self, max_modified: Option<glib::DateTime>) {
+            if *self.max_modified.borrow() == max_modified {
+                return;
+            }
+
+            *self.max_modified.borrow_mut() = max_modified;
+
+            let filter = self.filtered_list.filter().unwrap();
+            filter.emit_by_name::<()>("changed", &[&gtk::FilterChange::Different]);
+            self.obj().notify_max_modified();
+        }
+
+        fn set_min_size(&self, min_size: u64) {
+            let old_size = self.min_size.replace(min_size);
+            if old_size == min_size {
+                return;
+            }
+
+            let strict = if old_size == 0 || (min_size != 0 && min_size > old_size) {
+                gtk::FilterChange::MoreStrict
+            } else {
+                gtk::FilterChange::LessStrict
+            };
+            let filter = self.filtered_list.filter().unwrap();
+            filter.emit_by_name::<()>("changed", &[&strict]);
+            self.obj().notify_min_size();
+        }
+
+        fn set_max_size(&self, max_size: u64) {
+            let old_size = self.max_size.replace(max_size);
+            if old_size == max_size {
+                return;
+            }
+
+            let strict = if old_size == 0 || (max_size != 0 && max_size < old_size) {
+                gtk::FilterChange::MoreStrict
+            } else {
+                gtk::FilterChange::LessStrict
+            };
+            let filter = self.filtered_list.filter().unwrap();
+            filter.emit_by_name::<()>("changed", &[&strict]);
+            self.obj().notify_max_size();
+        }
+
+        fn set_glob_filter(&self, glob_filter: Option<String>) {
+            let glob_filter = glob_filter.filter(|glob| !glob.trim().is_empty());
+
+            if *self.glob_filter.borrow() == glob_filter {
+                return;
+            }
+
+            glib::g_debug!(LOG_DOMAIN, "Setting glob filter to {glob_filter:#?}");
+            *self.glob_file_filter.borrow_mut() = glob_filter.as_ref().map(|glob| {
+                let filter = gtk::FileFilter::new();
+                filter.add_pattern(glob.trim());
+                filter
+            });
+            *self.glob_filter.borrow_mut() = glob_filter;
+
+            let filter = self.filtered_list.filter().unwrap();
+            filter.emit_by_name::<()>("changed", &[&gtk::FilterChange::Different]);
+            self.obj().notify_glob_filter();
+        }
+
         fn set_search_term(&self, search_term: Option<String>) {
             let strict;
             let obj = self.obj();
@@ -342,6 +906,7 @@ mod imp {
 
         fn on_thumbnail_files_ready(
             &self,
+            files: Vec<String>,
             result: std::result::Result<glib::Variant, glib::Error>,
         ) {
             if result.is_ok() {
@@ -349,33 +914,196 @@ mod imp {
             }
 
             let error = result.err().unwrap();
+            if error.matches(gio::IOErrorEnum::Cancelled) {
+                return;
+            }
+
+            {
+                let mut in_flight = self.thumbnails_in_flight.borrow_mut();
+                for uri in &files {
+                    in_flight.remove(uri);
+                }
+            }
+
             if let Some(dbus_error) = error.kind::<gio::DBusError>() {
-                if dbus_error != gio::DBusError::ServiceUnknown {
-                    glib::g_warning!(LOG_DOMAIN, "ThumbnailFiles failed: {error}");
+                if dbus_error == gio::DBusError::ServiceUnknown {
+                    // No thumbnailer around, stick with the icons
+                    self.set_thumbnailer_available(false);
+                    let no_thumbnails = self.no_thumbnails.borrow();
+                    files
+                        .iter()
+                        .filter_map(|uri| no_thumbnails.get(uri))
+                        .for_each(|item| item.set_thumbnail_pending(false));
+                    return;
+                }
+                glib::g_warning!(LOG_DOMAIN, "ThumbnailFiles failed: {error}");
+            }
+
+            let failed = {
+                let mut no_thumbnails = self.no_thumbnails.borrow_mut();
+                files
+                    .into_iter()
+                    .filter_map(|uri| no_thumbnails.remove_entry(&uri))
+                    .collect()
+            };
+            self.retry_thumbnails(failed);
+        }
+
+        // Re-enqueues thumbnails that failed transiently with an increasing
+        // delay until we run out of retries
+        fn retry_thumbnails(&self, failed: Vec<(String, GridItem)>) {
+            let retries = self.thumbnail_retries.get();
+            let mut attempts = self.thumbnail_attempts.borrow_mut();
+            let mut retry = Vec::new();
+            let mut max_attempt = 0;
+
+            for (uri, item) in failed {
+                let attempt = attempts.entry(uri.clone()).or_default();
+                *attempt += 1;
+                if *attempt > retries {
+                    glib::g_debug!(LOG_DOMAIN, "Giving up on thumbnail for {uri}");
+                    attempts.remove(&uri);
+                    item.set_thumbnail_pending(false);
+                    continue;
                 }
+                max_attempt = max_attempt.max(*attempt);
+                retry.push((uri, item));
+            }
+
+            if retry.is_empty() {
+                return;
             }
+
+            let cancellable = self.thumbnails_cancellable.borrow().clone();
+            glib::source::timeout_add_seconds_local_once(
+                THUMBNAILS_DEBOUNCE_SECS << max_attempt,
+                glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move || {
+                        // The folder changed in the meantime
+                        if cancellable.is_cancelled() {
+                            return;
+                        }
+
+                        {
+                            let mut no_thumbnails = this.no_thumbnails.borrow_mut();
+                            for (uri, item) in retry {
+                                // The item got recycled in the meantime
+                                if grid_item_uri(&item).as_deref() != Some(uri.as_str()) {
+                                    continue;
+                                }
+                                no_thumbnails.insert(uri, item);
+                            }
+                        }
+                        this.send_for_thumbnailing();
+                    }
+                ),
+            );
         }
 
         pub fn send_for_thumbnailing(&self) {
+            self.thumbnail_batch.borrow_mut().reset();
+
+            if !self.thumbnailer_available.get() {
+                return;
+            }
+
+            if self.thumbnailer_proxy.borrow().is_none() {
+                return;
+            }
+
+            let mut in_flight = self.thumbnails_in_flight.borrow_mut();
+            // Don't ask again for what was already requested
+            let mut files: Vec<(String, f32)> = self
+                .no_thumbnails
+                .borrow()
+                .iter()
+                .filter(|(uri, _)| !in_flight.contains(*uri))
+                .map(|(uri, item)| (uri.clone(), self.distance_to_viewport(item)))
+                .collect();
+            // Request what the user is looking at first
+            files.sort_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+            let files: Vec<String> = files.into_iter().map(|(uri, _)| uri).collect();
+            in_flight.extend(files.iter().cloned());
+            drop(in_flight);
+
+            // Keep D-Bus messages reasonably sized in large folders
+            for (i, chunk) in files.chunks(THUMBNAILS_MAX_FILES_PER_CALL).enumerate() {
+                let chunk = chunk.to_vec();
+                if i == 0 {
+                    self.thumbnail_files(chunk);
+                    continue;
+                }
+
+                let cancellable = self.thumbnails_cancellable.borrow().clone();
+                glib::timeout_add_local_once(
+                    std::time::Duration::from_millis(THUMBNAILS_CALL_DELAY_MS * i as u64),
+                    glib::clone!(
+                        #[weak(rename_to = this)]
+                        self,
+                        move || {
+                            // The folder changed in the meantime
+                            if cancellable.is_cancelled() {
+                                return;
+                            }
+                            this.thumbnail_files(chunk);
+                        }
+                    ),
+                );
+            }
+        }
+
+        // Vertical distance of the item's center to the center of the
+        // visible area, items that aren't shown come last
+        fn distance_to_viewport(&self, item: &GridItem) -> f32 {
+            let obj = self.obj();
+            let Some(bounds) = item.compute_bounds(&*obj) else {
+                return f32::MAX;
+            };
+
+            (bounds.center().y() - obj.height() as f32 / 2.0).abs()
+        }
+
+        // Asks the thumbnailer for the given files that are still shown
+        fn thumbnail_files(&self, mut files: Vec<String>) {
             let proxy = self.thumbnailer_proxy.borrow();
             let Some(ref proxy) = *proxy else {
+                self.thumbnails_in_flight.borrow_mut().clear();
                 return;
             };
 
-            let files: Vec<String> = self.no_thumbnails.borrow().keys().cloned().collect();
+            {
+                let no_thumbnails = self.no_thumbnails.borrow();
+                let mut in_flight = self.thumbnails_in_flight.borrow_mut();
+                files.retain(|uri| match no_thumbnails.get(uri) {
+                    Some(item) => {
+                        item.set_thumbnail_pending(true);
+                        true
+                    }
+                    None => {
+                        in_flight.remove(uri);
+                        false
+                    }
+                });
+            }
+            if files.is_empty() {
+                return;
+            }
+
             let options: HashMap<&str, glib::Variant> = HashMap::new();
-            let params = (files, options).to_variant();
+            let params = (files.clone(), options).to_variant();
             proxy.call(
                 "ThumbnailFiles",
                 Some(&params),
                 gio::DBusCallFlags::NONE,
                 -1,
-                Some(&*self.cancellable.borrow()),
+                Some(&*self.thumbnails_cancellable.borrow()),
                 glib::clone!(
                     #[weak(rename_to = this)]
                     self,
                     move |result: std::result::Result<glib::Variant, glib::Error>| this
-                        .on_thumbnail_files_ready(result)
+                        .on_thumbnail_files_ready(files, result)
                 ),
             );
         }
@@ -386,15 +1114,38 @@ mod imp {
                 HashMap<String, glib::Variant>,
             )>::from_variant(&params)
             .unwrap_or_default();
-            let mut no_thumbnails = self.no_thumbnails.borrow_mut();
+            let mut failed = Vec::new();
 
-            for (file_uri, value_var) in &thumbnails {
-                if let Some(item) = no_thumbnails.remove(file_uri) {
-                    if let Some(path) = String::from_variant(value_var) {
-                        item.set_thumbnail(path);
+            {
+                let mut no_thumbnails = self.no_thumbnails.borrow_mut();
+                let mut attempts = self.thumbnail_attempts.borrow_mut();
+                let mut in_flight = self.thumbnails_in_flight.borrow_mut();
+
+                for (file_uri, value_var) in &thumbnails {
+                    in_flight.remove(file_uri);
+
+                    // Late result for a file that's not (or no longer) shown
+                    let Some(item) = no_thumbnails.remove(file_uri) else {
+                        continue;
+                    };
+
+                    match String::from_variant(value_var) {
+                        // An empty path means the thumbnailer failed for good,
+                        // keep the generic icon
+                        Some(path) if path.is_empty() => {
+                            attempts.remove(file_uri);
+                            item.set_thumbnail_pending(false);
+                        }
+                        Some(path) if std::path::Path::new(&path).exists() => {
+                            attempts.remove(file_uri);
+                            item.set_thumbnail(path);
+                        }
+                        _ => failed.push((file_uri.clone(), item)),
                     }
                 }
             }
+
+            self.retry_thumbnails(failed);
         }
 
         fn on_proxy_ready(&self, result: std::result::Result<gio::DBusProxy, glib::Error>) {
@@ -413,30 +1164,76 @@ mod imp {
                                 .on_thumbnailing_done(params)
                         ),
                     );
-                    *self.thumbnailer_proxy.borrow_mut() = Some(proxy);
+                    proxy.connect_notify_local(
+                        Some("g-name-owner"),
+                        glib::clone!(
+                            #[weak(rename_to = this)]
+                            self,
+                            move |proxy, _| this.on_thumbnailer_owner_changed(proxy)
+                        ),
+                    );
+                    *self.thumbnailer_proxy.borrow_mut() = Some(proxy.clone());
+
+                    // The proxy tried to activate the service on construction
+                    // already so a missing owner means there's no thumbnailer
+                    if proxy.name_owner().is_some() {
+                        self.on_thumbnailer_owner_changed(&proxy);
+                    } else {
+                        glib::g_message!(
+                            LOG_DOMAIN,
+                            "Thumbnailer {} not available",
+                            self.thumbnailer_name.borrow()
+                        );
+                        self.set_thumbnailer_available(false);
+                    }
                 }
                 Err(error) => {
                     glib::g_message!(LOG_DOMAIN, "Failed to load thumbnailer: {error}");
+                    self.set_thumbnailer_available(false);
                 }
             }
         }
-    }
-
-    #[glib::derived_properties]
-    impl ObjectImpl for DirView {
-        fn constructed(&self) {
-            self.parent_constructed();
-            let obj = self.obj();
 
-            *self.cancellable.borrow_mut() = gio::Cancellable::new();
+        fn on_thumbnailer_owner_changed(&self, proxy: &gio::DBusProxy) {
+            // The thumbnailer may exit when idle, the next call activates
+            // it again. Only a failing call marks it as unavailable.
+            if proxy.name_owner().is_none() {
+                // Requests it didn't finish are lost
+                self.thumbnails_in_flight.borrow_mut().clear();
+                return;
+            }
+            self.set_thumbnailer_available(true);
+
+            // Catch up on the items that showed up meanwhile
+            if !self.no_thumbnails.borrow().is_empty() {
+                self.send_for_thumbnailing();
+            }
+        }
+
+        fn set_thumbnailer_available(&self, available: bool) {
+            if self.thumbnailer_available.replace(available) == available {
+                return;
+            }
+
+            self.obj().notify_thumbnailer_available();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for DirView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            *self.cancellable.borrow_mut() = gio::Cancellable::new();
 
             gio::DBusProxy::for_bus(
                 gio::BusType::Session,
                 gio::DBusProxyFlags::NONE,
                 None,
-                THUMBNAILER_NAME,
-                THUMBNAILER_PATH,
-                THUMBNAILER_IFACE,
+                &self.thumbnailer_name.borrow(),
+                &self.thumbnailer_path.borrow(),
+                &self.thumbnailer_iface.borrow(),
                 Some(&*self.cancellable.borrow()),
                 glib::clone!(
                     #[weak(rename_to = this)]
@@ -447,17 +1244,35 @@ mod imp {
             );
 
             obj.setup_gsettings();
-            obj.set_directories_first(true);
             obj.setup_sort_and_filter();
+            obj.setup_column_view_sorting();
+            obj.setup_letter_index();
+            obj.setup_drop_target();
+            obj.clipboard().connect_changed(glib::clone!(
+                #[weak]
+                obj,
+                move |_| obj.update_can_paste()
+            ));
             obj.on_n_items_changed();
 
             obj.bind_property("folder", &self.directory_list.get(), "file")
                 .sync_create()
                 .build();
+
+            self.directory_list.connect_items_changed(glib::clone!(
+                #[weak]
+                obj,
+                move |_, _, removed, _| {
+                    if removed > 0 {
+                        obj.on_items_removed();
+                    }
+                }
+            ));
         }
 
         fn dispose(&self) {
             self.cancellable.borrow().cancel();
+            self.thumbnails_cancellable.borrow().cancel();
         }
 
         fn signals() -> &'static [Signal] {
@@ -472,6 +1287,11 @@ mod imp {
                     Signal::builder("new-filename")
                         .param_types([String::static_type()])
                         .build(),
+                    // The displayed folder changed, no matter how. The
+                    // URI is empty when the folder got cleared.
+                    Signal::builder("folder-changed")
+                        .param_types([String::static_type()])
+                        .build(),
                 ]
             })
         }
@@ -521,23 +1341,161 @@ impl DirView {
             .sync_create()
             .build();
 
+        self.bind_property("show-details", &grid_item, "show-details")
+            .sync_create()
+            .build();
+
+        self.bind_property("item-details", &grid_item, "details")
+            .sync_create()
+            .build();
+
         list_item.set_child(Some(&grid_item));
     }
 
+    #[template_callback]
+    fn on_type_ahead_key_pressed(
+        &self,
+        keyval: gdk::Key,
+        _keycode: u32,
+        state: gdk::ModifierType,
+    ) -> bool {
+        let imp = self.imp();
+
+        if !imp.type_ahead.get() {
+            return false;
+        }
+
+        if keyval == gdk::Key::Escape {
+            if imp.type_ahead_prefix.borrow().is_empty() {
+                return false;
+            }
+            self.reset_type_ahead();
+            return true;
+        }
+
+        let modifiers = gdk::ModifierType::CONTROL_MASK
+            | gdk::ModifierType::ALT_MASK
+            | gdk::ModifierType::SUPER_MASK;
+        if state.intersects(modifiers) {
+            return false;
+        }
+
+        let Some(c) = keyval.to_unicode().filter(|c| !c.is_control()) else {
+            return false;
+        };
+
+        // A leading space should still activate the focused item
+        if c == ' ' && imp.type_ahead_prefix.borrow().is_empty() {
+            return false;
+        }
+
+        imp.type_ahead_prefix.borrow_mut().extend(c.to_lowercase());
+        if let Some(source_id) = imp.type_ahead_timeout_id.take() {
+            source_id.remove();
+        }
+        let source_id = glib::timeout_add_local_once(
+            std::time::Duration::from_millis(TYPE_AHEAD_TIMEOUT_MS),
+            glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move || {
+                    this.imp().type_ahead_timeout_id.take();
+                    this.reset_type_ahead();
+                }
+            ),
+        );
+        imp.type_ahead_timeout_id.replace(Some(source_id));
+
+        self.type_ahead_find();
+        true
+    }
+
+    #[template_callback]
+    fn on_list_item_setup(&self, object: glib::Object) {
+        let cell = object.downcast_ref::<gtk::ColumnViewCell>().unwrap();
+        let grid_item = GridItem::new();
+
+        grid_item.set_compact(true);
+        self.bind_property("thumbnail-mode", &grid_item, "thumbnail-mode")
+            .sync_create()
+            .build();
+
+        cell.set_child(Some(&grid_item));
+    }
+
+    #[template_callback]
+    fn on_label_item_setup(&self, object: glib::Object) {
+        let cell = object.downcast_ref::<gtk::ColumnViewCell>().unwrap();
+        let label = gtk::Label::builder()
+            .xalign(0.0)
+            .css_classes(["dim-label", "numeric"])
+            .build();
+
+        cell.set_child(Some(&label));
+    }
+
+    fn cell_info_and_label(object: &glib::Object) -> (gio::FileInfo, gtk::Label) {
+        let (item, child) = list_item_parts(object);
+
+        (
+            item.and_downcast::<gio::FileInfo>().unwrap(),
+            child.and_downcast::<gtk::Label>().unwrap(),
+        )
+    }
+
+    #[template_callback]
+    fn on_size_item_bind(&self, object: glib::Object) {
+        let (info, label) = Self::cell_info_and_label(&object);
+
+        if self.is_directory(&info) {
+            label.set_label("");
+        } else {
+            label.set_label(&util::format_size(info.size() as u64));
+        }
+    }
+
+    #[template_callback]
+    fn on_modified_item_bind(&self, object: glib::Object) {
+        let (info, label) = Self::cell_info_and_label(&object);
+
+        let modified = info.modification_date_time();
+        let relative = modified
+            .as_ref()
+            .and_then(util::format_relative_date_time)
+            .unwrap_or_default();
+        label.set_label(&relative);
+
+        // Exact time for those who need it
+        let exact = modified
+            .and_then(|modified| modified.to_local().ok())
+            .and_then(|modified| modified.format("%x %R").ok());
+        label.set_tooltip_text(exact.as_deref());
+    }
+
     #[template_callback]
     fn on_item_bind(&self, object: glib::Object) {
-        let list_item = object.downcast_ref::<gtk::ListItem>().unwrap();
-        let item = list_item.item().unwrap();
+        let (item, widget) = list_item_parts(&object);
+        let item = item.unwrap();
         let info = item.downcast_ref::<gio::FileInfo>().unwrap();
 
-        let widget = list_item.child().unwrap();
+        let widget = widget.unwrap();
         let grid_item = widget.downcast_ref::<GridItem>().unwrap();
 
         grid_item.set_fileinfo(info);
 
-        if info.boolean(gio::FILE_ATTRIBUTE_THUMBNAIL_IS_VALID)
-            || self.thumbnail_mode() == ThumbnailMode::Never
-        {
+        if self.thumbnail_mode() == ThumbnailMode::Never {
+            return;
+        }
+
+        // The grid item already picked up a cached thumbnail, no need
+        // to ask the thumbnailer
+        if util::cached_thumbnail_path(info).is_some() {
+            return;
+        }
+
+        let binding = info.attribute_object("standard::file").unwrap();
+        let file = binding.downcast_ref::<gio::File>().unwrap();
+        if self.thumbnail_mode() == ThumbnailMode::Local && !file.is_native() {
             return;
         }
 
@@ -547,10 +1505,15 @@ impl DirView {
             source_id.remove();
         }
 
-        let mut no_thumbnails = imp.no_thumbnails.borrow_mut();
-        let binding = info.attribute_object("standard::file").unwrap();
-        let file = binding.downcast_ref::<gio::File>().unwrap();
-        no_thumbnails.insert(file.uri().to_string(), grid_item.clone());
+        imp.no_thumbnails
+            .borrow_mut()
+            .insert(file.uri().to_string(), grid_item.clone());
+
+        // Don't let continuous binding (e.g. while scrolling) starve the thumbnailer
+        if imp.thumbnail_batch.borrow_mut().add(glib::monotonic_time()) {
+            imp.send_for_thumbnailing();
+            return;
+        }
 
         let source_id = glib::source::timeout_add_seconds_local_once(
             THUMBNAILS_DEBOUNCE_SECS,
@@ -566,26 +1529,63 @@ impl DirView {
         *imp.debounce_id.borrow_mut() = Some(source_id);
     }
 
+    #[template_callback]
+    fn on_item_unbind(&self, object: glib::Object) {
+        let (item, widget) = list_item_parts(&object);
+        let Some(item) = item else {
+            return;
+        };
+        let info = item.downcast_ref::<gio::FileInfo>().unwrap();
+        let Some(binding) = info.attribute_object("standard::file") else {
+            return;
+        };
+        let file = binding.downcast_ref::<gio::File>().unwrap();
+        let imp = self.imp();
+
+        // The item scrolled out of view, so don't ask for its thumbnail
+        // anymore. The list item widget gets recycled so make sure we
+        // only drop the entry if it still belongs to this widget.
+        let mut no_thumbnails = imp.no_thumbnails.borrow_mut();
+        let uri = file.uri().to_string();
+        let is_ours = no_thumbnails.get(&uri).is_some_and(|grid_item| {
+            Some(grid_item.upcast_ref::<gtk::Widget>()) == widget.as_ref()
+        });
+        if is_ours {
+            no_thumbnails.remove(&uri);
+        }
+
+        if no_thumbnails.is_empty() {
+            if let Some(source_id) = imp.debounce_id.take() {
+                source_id.remove();
+            }
+        }
+    }
+
     #[template_callback]
     fn on_selection_changed(&self, position: u32, n_items: u32) {
         glib::g_debug!(LOG_DOMAIN, "Selection changed {position:#?} {n_items:#?}");
+        self.imp().update_selection_count();
 
         let selection = self.imp().single_selection.get();
         let selected_item = selection.selected_item();
         let mut is_selected = false;
+        let mut selected_file = None;
 
         if let Some(info) = selected_item {
             let fileinfo = info.downcast_ref::<gio::FileInfo>().unwrap();
             let object = fileinfo.attribute_object("standard::file").unwrap();
             let file = object.downcast_ref::<gio::File>().unwrap();
 
-            if self.is_directory(fileinfo) {
-                let uri = file.uri();
+            if self.is_directory(fileinfo) && self.select_directories() && self.directories_only() {
+                glib::g_debug!(LOG_DOMAIN, "Selected subfolder {}", file.uri());
+            } else if self.is_directory(fileinfo) {
+                let uri = util::target_uri(fileinfo, file);
 
                 glib::g_debug!(LOG_DOMAIN, "Should open {uri:#?}");
                 self.emit_by_name::<()>("new-uri", &[&uri]);
-            } else {
+            } else if !util::is_in_trash(file) {
                 is_selected = true;
+                selected_file = Some(file.clone());
                 let filename = file.basename();
                 self.imp()
                     .obj()
@@ -593,27 +1593,155 @@ impl DirView {
             }
         }
 
+        self.imp().selected_file.replace(selected_file);
+
         if self.directories_only() {
+            self.imp().update_directory_selection();
             return;
         }
 
         self.imp().set_has_selection(is_selected);
     }
 
+    // The selection model doesn't emit `selection-changed` when the
+    // selected item goes away so check whether it's still there
+    fn on_items_removed(&self) {
+        let imp = self.imp();
+
+        if imp.single_selection.selected_item().is_some() {
+            return;
+        }
+
+        if self.directories_only() {
+            imp.update_directory_selection();
+            return;
+        }
+
+        let Some(file) = imp.selected_file.take() else {
+            return;
+        };
+
+        imp.set_has_selection(false);
+
+        // Changing folders or reloading clears the list too
+        let removed = imp
+            .directory_list
+            .file()
+            .zip(file.parent())
+            .is_some_and(|(dir, parent)| dir.equal(&parent) && !imp.directory_list.is_loading());
+
+        // The undo toast already tells about our own operations
+        if removed && !self.take_own_removal(&file) {
+            glib::g_debug!(LOG_DOMAIN, "Selected {} got removed", file.uri());
+            self.show_toast(&gettextrs::gettext("The selected file was removed"));
+        }
+    }
+
     #[template_callback]
     fn on_n_items_changed(&self) {
+        self.imp().update_selection_count();
         let n_items = self.imp().filtered_list.get().n_items();
-        let pagename = if n_items > 0 { "folder" } else { "empty" };
-        self.imp().view_stack.get().set_visible_child_name(pagename);
+        if self.imp().n_items.replace(n_items) != n_items {
+            self.notify_n_items();
+            self.announce_search_results();
+        }
+        self.update_view_page();
+        self.update_letter_index();
+        self.update_filtered_out();
+    }
+
+    // Let screen reader users know how the search narrowed things down
+    fn announce_search_results(&self) {
+        let imp = self.imp();
+        if imp.display_mode.get() != DisplayMode::Search || imp.directory_list.is_loading() {
+            return;
+        }
+
+        let n_items = imp.n_items.get();
+        let msg = if n_items == 0 {
+            gettextrs::gettext("No results")
+        } else {
+            gettextrs::ngettext("{} result", "{} results", n_items).replacen(
+                "{}",
+                &n_items.to_string(),
+                1,
+            )
+        };
+        self.announce(&msg, gtk::AccessibleAnnouncementPriority::Medium);
+    }
+
+    fn update_view_page(&self) {
+        let imp = self.imp();
+        // Keep the (empty) folder page during the loading grace period and
+        // while refreshing as there's a spinner already
+        let pagename = if imp.n_items.get() > 0
+            || imp.loading_grace_id.borrow().is_some()
+            || imp.refresh_spinner.is_visible()
+        {
+            "folder"
+        } else {
+            "empty"
+        };
+        imp.view_stack.set_visible_child_name(pagename);
+    }
+
+    // Distinguish folders that are really empty from ones where all
+    // items (or only hidden files) got filtered out
+    fn update_filtered_out(&self) {
+        let imp = self.imp();
+        let filtered_out = imp.filtered_list.n_items() == 0 && imp.directory_list.n_items() > 0;
+        if imp.filtered_out.replace(filtered_out) != filtered_out {
+            self.notify_filtered_out();
+        }
+
+        let has_hidden_files = filtered_out
+            && !imp.show_hidden.get()
+            && imp
+                .directory_list
+                .iter::<gio::FileInfo>()
+                .flatten()
+                .any(|info| util::is_hidden(&info) || util::is_backup(&info));
+
+        if imp.has_hidden_files.replace(has_hidden_files) != has_hidden_files {
+            self.notify_has_hidden_files();
+        }
+    }
+
+    #[template_callback]
+    fn on_transfer_cancel_clicked(&self) {
+        let cancellables = self.imp().transfer_cancellables.borrow().clone();
+        for cancellable in cancellables {
+            cancellable.cancel();
+        }
     }
 
     #[template_callback]
     fn on_activate(&self, pos: u32) {
         glib::g_debug!(LOG_DOMAIN, "Item Activated {pos:#?}");
 
+        // Tapping selected the folder already, so enter it
+        if self.directories_only() && self.select_directories() {
+            let info = self
+                .imp()
+                .single_selection
+                .item(pos)
+                .and_downcast::<gio::FileInfo>();
+            if let Some(info) = info.filter(|info| self.is_directory(info)) {
+                let file = info
+                    .attribute_object("standard::file")
+                    .and_downcast::<gio::File>()
+                    .unwrap();
+                let uri = util::target_uri(&info, &file);
+
+                glib::g_debug!(LOG_DOMAIN, "Should open {uri:#?}");
+                self.emit_by_name::<()>("new-uri", &[&uri]);
+                return;
+            }
+        }
+
         self.imp().single_selection.set_selected(pos);
-        // Only accept when we have a selection
-        if !self.has_selection() {
+        // Only accept when we have a selection and the embedder wants us to
+        if !self.has_selection() || self.file_activation() == ActivateAction::Select {
             return;
         }
 
@@ -624,7 +1752,9 @@ impl DirView {
     fn searching_to_status_page_icon(&self) -> &str {
         match self.display_mode() {
             DisplayMode::Search => "nautilus-folder-search-symbolic",
-            DisplayMode::Content | DisplayMode::Loading => "folder-symbolic",
+            DisplayMode::Content | DisplayMode::Loading | DisplayMode::NoFolder => {
+                "folder-symbolic"
+            }
         }
     }
 
@@ -632,70 +1762,310 @@ impl DirView {
     fn searching_to_status_page_title(&self) -> String {
         match self.display_mode() {
             DisplayMode::Search => gettextrs::gettext("Search is empty"),
+            DisplayMode::Content if self.filtered_out() => gettextrs::gettext("No matching files"),
             DisplayMode::Content => gettextrs::gettext("Folder is empty"),
             DisplayMode::Loading => gettextrs::gettext("Folder is loading…"),
+            DisplayMode::NoFolder => gettextrs::gettext("No folder selected"),
         }
     }
 
     #[template_callback]
     fn on_loading_changed(&self) {
+        let imp = self.imp();
+        if let Some(source_id) = imp.loading_grace_id.take() {
+            source_id.remove();
+        }
+
+        if !imp.directory_list.is_loading() {
+            imp.refresh_spinner.set_visible(false);
+        }
+
+        if imp.directory_list.is_loading() && imp.display_mode.get() != DisplayMode::Loading {
+            let source_id = glib::timeout_add_local_once(
+                std::time::Duration::from_millis(LOADING_GRACE_MS),
+                glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move || {
+                        this.imp().loading_grace_id.take();
+                        this.update_display_mode();
+                    }
+                ),
+            );
+            imp.loading_grace_id.replace(Some(source_id));
+            self.update_view_page();
+            return;
+        }
+
+        self.update_display_mode();
+        self.focus_first_item();
+    }
+
+    // Put keyboard focus on the first item of a newly entered folder
+    // without selecting it
+    fn focus_first_item(&self) {
+        let imp = self.imp();
+        if imp.directory_list.is_loading() || !imp.focus_on_load.replace(false) {
+            return;
+        }
+
+        // Don't steal the focus from the search or any other entry
+        let in_text = self
+            .root()
+            .and_then(|root| root.focus())
+            .is_some_and(|widget| widget.is::<gtk::Text>());
+        let searching = imp
+            .search_term
+            .borrow()
+            .as_ref()
+            .is_some_and(|term| !term.is_empty());
+        // An item is about to get selected and focused
+        let selecting = imp.select_item_id.borrow().is_some();
+
+        if in_text || searching || selecting || imp.single_selection.n_items() == 0 {
+            return;
+        }
+
+        self.scroll_to(0, gtk::ListScrollFlags::FOCUS);
+    }
+
+    fn update_display_mode(&self) {
         let mode = if self.imp().directory_list.is_loading() {
             DisplayMode::Loading
+        } else if self.folder().is_none() {
+            DisplayMode::NoFolder
         } else {
             DisplayMode::Content
         };
         self.imp().display_mode.replace(mode);
         self.notify_display_mode();
+        self.update_view_page();
+        // A folder with only filtered out files doesn't change the number of shown items
+        self.update_filtered_out();
     }
 
     #[template_callback]
-    fn loading_to_status_page_spinner(&self) -> bool {
-        matches!(self.display_mode(), DisplayMode::Loading)
+    fn status_page_description(&self) -> Option<String> {
+        if !self.filtered_out() {
+            return None;
+        }
+
+        let description = if self.has_hidden_files() {
+            gettextrs::gettext("All files in this folder are hidden")
+        } else {
+            gettextrs::gettext("Adjust the filters to see more files")
+        };
+        Some(description)
     }
 
-    pub fn selected(&self) -> Option<Vec<String>> {
-        let vec = if self.directories_only() {
-            match self.folder().unwrap().path() {
-                None => return None,
-                Some(_) => vec![self.folder().unwrap().uri().to_string()],
-            }
+    #[template_callback]
+    fn status_page_can_clear_filters(&self) -> bool {
+        self.filtered_out() && self.has_active_filters()
+    }
+
+    #[template_callback]
+    fn status_page_can_create_folder(&self) -> bool {
+        matches!(self.display_mode(), DisplayMode::Content)
+            && self.folder().is_some_and(|folder| folder.path().is_some())
+    }
+
+    #[template_callback]
+    fn on_swipe_begin(&self, _sequence: Option<gdk::EventSequence>, gesture: gtk::GestureSwipe) {
+        let start_x = gesture.bounding_box_center().map(|(x, _)| x);
+        self.imp().swipe_start_x.set(start_x);
+        self.imp().touching.set(true);
+    }
+
+    #[template_callback]
+    fn on_swipe_end(&self) {
+        self.imp().touching.set(false);
+    }
+
+    #[template_callback]
+    fn on_swipe(&self, velocity_x: f64, velocity_y: f64) {
+        let Some(start_x) = self.imp().swipe_start_x.take() else {
+            return;
+        };
+
+        if start_x < SWIPE_EDGE_PX || start_x > self.width() as f64 - SWIPE_EDGE_PX {
+            return;
+        }
+
+        // Like going back in a browser
+        let velocity_x = if self.direction() == gtk::TextDirection::Rtl {
+            -velocity_x
         } else {
-            let selected = self.imp().single_selection.get().selected_item();
-            let item = selected?;
+            velocity_x
+        };
+        if velocity_x < SWIPE_BACK_MIN_VELOCITY || velocity_x < 2.0 * velocity_y.abs() {
+            return;
+        }
 
-            let file = item
-                .downcast_ref::<gio::FileInfo>()
-                .unwrap()
-                .attribute_object("standard::file")
-                .unwrap();
+        self.go_up();
+    }
 
-            let uri = file.downcast_ref::<gio::File>().unwrap().uri();
-            glib::g_debug!(LOG_DOMAIN, "Uri {uri:#?}");
+    /// Whether there's a parent folder to go up to.
+    pub fn can_go_up(&self) -> bool {
+        self.folder().and_then(|folder| folder.parent()).is_some()
+    }
 
-            vec![uri.to_string()]
+    /// Asks to show the parent folder, there's none at the root of a file
+    /// system or of virtual locations like `recent:///`.
+    pub fn go_up(&self) {
+        let Some(parent) = self.folder().and_then(|folder| folder.parent()) else {
+            return;
         };
-        Some(vec)
+
+        let uri = parent.uri();
+        glib::g_debug!(LOG_DOMAIN, "Going up to {uri}");
+        self.emit_by_name::<()>("new-uri", &[&uri]);
     }
 
-    fn sort_by_name(&self, info1: &gio::FileInfo, info2: &gio::FileInfo) -> gtk::Ordering {
-        match info1.display_name().cmp(&info2.display_name()) {
-            Ordering::Less => {
-                if self.imp().reversed.get() {
-                    return gtk::Ordering::Larger;
-                }
-                gtk::Ordering::Smaller
-            }
-            Ordering::Greater => {
-                if self.imp().reversed.get() {
-                    return gtk::Ordering::Smaller;
-                }
-                gtk::Ordering::Larger
-            }
-            Ordering::Equal => gtk::Ordering::Equal,
+    #[template_callback]
+    fn on_zoom_begin(&self) {
+        let imp = self.imp();
+        imp.zoom_start_size.set(imp.icon_size.get());
+    }
+
+    #[template_callback]
+    fn on_zoom_scale_changed(&self, scale: f64) {
+        let wanted = (self.imp().zoom_start_size.get() as f64 * scale).round() as u32;
+        // Snap to the sizes offered in the menu so pinching doesn't
+        // relayout the grid on every tiny finger movement
+        let size = ICON_SIZES
+            .iter()
+            .copied()
+            .min_by_key(|size| size.abs_diff(wanted))
+            .unwrap();
+
+        if size != self.icon_size() {
+            glib::g_debug!(LOG_DOMAIN, "Icon size changed to {size}");
+            self.set_icon_size(size);
         }
     }
 
-    fn sort_by_modification_time(
+    #[template_callback]
+    fn on_edge_overshot(&self, position: gtk::PositionType) {
+        // Pull to refresh
+        if position != gtk::PositionType::Top || self.imp().directory_list.is_loading() {
+            return;
+        }
+
+        // Scrolling to the top with a mouse wheel or touchpad isn't a pull
+        if !self.imp().touching.get() {
+            return;
+        }
+
+        if self.folder().is_none() {
+            return;
+        }
+
+        // Hidden again once loading finished
+        self.imp().refresh_spinner.set_visible(true);
+        self.reload();
+    }
+
+    #[template_callback]
+    fn loading_to_status_page_spinner(&self) -> bool {
+        matches!(self.display_mode(), DisplayMode::Loading)
+    }
+
+    /// Reloads the folder's content (e.g. when the file monitor
+    /// missed changes) and regenerates thumbnails.
+    pub fn reload(&self) {
+        let imp = self.imp();
+        let Some(folder) = self.folder() else {
+            return;
+        };
+
+        glib::g_debug!(LOG_DOMAIN, "Reloading {}", folder.uri());
+        imp.reset_thumbnails();
+        imp.directory_list.set_file(None::<&gio::File>);
+        imp.directory_list.set_file(Some(&folder));
+    }
+
+    // The selected subfolder when directories can be selected by tapping
+    fn selected_subfolder(&self) -> Option<(gio::FileInfo, gio::File)> {
+        if !self.directories_only() || !self.select_directories() {
+            return None;
+        }
+
+        let info = self
+            .imp()
+            .single_selection
+            .selected_item()
+            .and_downcast::<gio::FileInfo>()
+            .filter(|info| self.is_directory(info))?;
+        let file = info
+            .attribute_object("standard::file")
+            .and_downcast::<gio::File>()?;
+
+        Some((info, file))
+    }
+
+    pub fn selected(&self) -> Option<Vec<String>> {
+        let vec = if let Some((info, file)) = self.selected_subfolder() {
+            vec![util::target_uri(&info, &file)]
+        } else if self.directories_only() {
+            let folder = self.folder()?;
+            if !util::is_valid_folder(Some(&folder)) {
+                return None;
+            }
+            vec![folder.uri().to_string()]
+        } else {
+            let selected = self.imp().single_selection.get().selected_item();
+            let item = selected?;
+
+            let info = item.downcast_ref::<gio::FileInfo>().unwrap();
+            let file = info.attribute_object("standard::file").unwrap();
+
+            let file = file.downcast_ref::<gio::File>().unwrap();
+            // Trashed files need to be restored before they can be used
+            if util::is_in_trash(file) {
+                return None;
+            }
+
+            // Hand out the real file for e.g. recent:///
+            let uri = util::target_uri(info, file);
+            glib::g_debug!(LOG_DOMAIN, "Uri {uri:#?}");
+
+            vec![uri]
+        };
+        Some(vec)
+    }
+
+    fn name_keys(&self, info: &gio::FileInfo) -> Rc<NameKeys> {
+        self.imp()
+            .name_keys
+            .borrow_mut()
+            .entry(info.display_name())
+            .or_insert_with_key(|name| Rc::new(NameKeys::new(name)))
+            .clone()
+    }
+
+    fn tie_break(&self, info1: &gio::FileInfo, info2: &gio::FileInfo) -> gtk::Ordering {
+        tie_break(info1, &self.name_keys(info1), info2, &self.name_keys(info2)).into()
+    }
+
+    fn sort_by_name(&self, info1: &gio::FileInfo, info2: &gio::FileInfo) -> gtk::Ordering {
+        match info1.display_name().cmp(&info2.display_name()) {
+            Ordering::Less => {
+                if self.imp().reversed.get() {
+                    return gtk::Ordering::Larger;
+                }
+                gtk::Ordering::Smaller
+            }
+            Ordering::Greater => {
+                if self.imp().reversed.get() {
+                    return gtk::Ordering::Smaller;
+                }
+                gtk::Ordering::Larger
+            }
+            Ordering::Equal => self.tie_break(info1, info2),
+        }
+    }
+
+    fn sort_by_modification_time(
         &self,
         info1: &gio::FileInfo,
         info2: &gio::FileInfo,
@@ -716,8 +2086,184 @@ impl DirView {
                 }
                 gtk::Ordering::Larger
             }
-            Ordering::Equal => gtk::Ordering::Equal,
+            Ordering::Equal => self.tie_break(info1, info2),
+        }
+    }
+
+    fn sort_by_size(&self, info1: &gio::FileInfo, info2: &gio::FileInfo) -> gtk::Ordering {
+        let ordering = info1.size().cmp(&info2.size());
+        let ordering = if self.imp().reversed.get() {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+
+        match ordering {
+            Ordering::Less => gtk::Ordering::Smaller,
+            Ordering::Greater => gtk::Ordering::Larger,
+            Ordering::Equal => self.tie_break(info1, info2),
+        }
+    }
+
+    fn sort_column(&self, sort_mode: SortMode) -> gtk::ColumnViewColumn {
+        let imp = self.imp();
+
+        match sort_mode {
+            SortMode::DisplayName => imp.name_column.get(),
+            SortMode::ModificationTime => imp.modified_column.get(),
+            SortMode::Size => imp.size_column.get(),
+        }
+    }
+
+    fn setup_column_view_sorting(&self) {
+        let imp = self.imp();
+
+        // The sorting happens in `sorted_list`, the column sorters only
+        // make the headers clickable
+        let sorter = imp.sorted_list.sorter();
+        for column in [&*imp.name_column, &*imp.size_column, &*imp.modified_column] {
+            column.set_sorter(sorter.as_ref());
+        }
+
+        let column_sorter = imp
+            .column_view
+            .sorter()
+            .and_downcast::<gtk::ColumnViewSorter>()
+            .unwrap();
+        column_sorter.connect_changed(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |column_sorter, _| this.on_column_sorter_changed(column_sorter)
+        ));
+
+        for name in ["sort-mode", "reversed"] {
+            self.connect_notify_local(Some(name), |this, _| this.sync_sort_headers());
+        }
+        self.sync_sort_headers();
+    }
+
+    fn on_column_sorter_changed(&self, column_sorter: &gtk::ColumnViewSorter) {
+        let imp = self.imp();
+
+        if imp.syncing_sort_headers.get() {
+            return;
+        }
+
+        let Some(column) = column_sorter.primary_sort_column() else {
+            return;
+        };
+
+        let sort_mode = if column == *imp.size_column {
+            SortMode::Size
+        } else if column == *imp.modified_column {
+            SortMode::ModificationTime
+        } else {
+            SortMode::DisplayName
+        };
+        let reversed = column_sorter.primary_sort_order() == gtk::SortType::Descending;
+
+        if sort_mode == self.sort_mode() && reversed == self.reversed() {
+            return;
+        }
+
+        // Let the file selector store the new sort order too
+        let enum_type = glib::EnumClass::with_type(SortMode::static_type()).unwrap();
+        let nick = enum_type.value(sort_mode as i32).unwrap().nick();
+        let target = (nick, reversed).to_variant();
+        if self
+            .activate_action("file-selector.sort", Some(&target))
+            .is_err()
+        {
+            self.set_sorting(sort_mode, reversed);
+        }
+    }
+
+    // Show the current sort column and direction in the column headers
+    fn sync_sort_headers(&self) {
+        let imp = self.imp();
+        let order = if self.reversed() {
+            gtk::SortType::Descending
+        } else {
+            gtk::SortType::Ascending
+        };
+
+        imp.syncing_sort_headers.set(true);
+        imp.column_view
+            .sort_by_column(Some(&self.sort_column(self.sort_mode())), order);
+        imp.syncing_sort_headers.set(false);
+    }
+
+    fn in_modified_range(&self, info: &gio::FileInfo) -> bool {
+        let imp = self.imp();
+        let min_modified = imp.min_modified.borrow();
+        let max_modified = imp.max_modified.borrow();
+
+        if min_modified.is_none() && max_modified.is_none() {
+            return true;
+        }
+
+        let Some(modified) = info.modification_date_time() else {
+            return false;
+        };
+
+        if min_modified.as_ref().is_some_and(|min| modified < *min) {
+            return false;
+        }
+
+        if max_modified.as_ref().is_some_and(|max| modified > *max) {
+            return false;
+        }
+
+        true
+    }
+
+    // Whether filters a user can clear are in effect
+    fn has_active_filters(&self) -> bool {
+        let imp = self.imp();
+
+        imp.search_term
+            .borrow()
+            .as_ref()
+            .is_some_and(|term| !term.is_empty())
+            || imp.glob_filter.borrow().is_some()
+            || imp.min_modified.borrow().is_some()
+            || imp.max_modified.borrow().is_some()
+            || imp.min_size.get() != 0
+            || imp.max_size.get() != 0
+    }
+
+    /// Clears the search term as well as the name, modification time and
+    /// size filters. The type filter and a custom filter are kept as these
+    /// are up to the embedder.
+    pub fn clear_filters(&self) {
+        glib::g_debug!(LOG_DOMAIN, "Clearing filters");
+        self.set_search_term("");
+        self.set_glob_filter(None::<&str>);
+        self.set_min_modified(None::<&glib::DateTime>);
+        self.set_max_modified(None::<&glib::DateTime>);
+        self.set_min_size(0);
+        self.set_max_size(0);
+    }
+
+    fn in_size_range(&self, info: &gio::FileInfo) -> bool {
+        let imp = self.imp();
+        let size = info.size() as u64;
+        let min_size = imp.min_size.get();
+        let max_size = imp.max_size.get();
+
+        if min_size != 0 && size < min_size {
+            return false;
         }
+
+        max_size == 0 || size <= max_size
+    }
+
+    fn matches_glob(&self, info: &gio::FileInfo) -> bool {
+        self.imp()
+            .glob_file_filter
+            .borrow()
+            .as_ref()
+            .is_none_or(|filter| filter.match_(info))
     }
 
     fn setup_sort_and_filter(&self) {
@@ -734,23 +2280,43 @@ impl DirView {
                     .downcast_ref::<gio::FileInfo>()
                     .expect("Should be file info");
 
-                if this.directories_first() {
+                // Grouping goes before the sort mode and by default isn't
+                // affected by reversing the sort
+                let directories_first = match this.directories_first_mode() {
+                    DirectoriesFirstMode::Always => Some(true),
+                    DirectoriesFirstMode::Never => None,
+                    DirectoriesFirstMode::FollowSort => Some(!this.reversed()),
+                };
+
+                if let Some(directories_first) = directories_first {
                     let is_dir1 = this.is_directory(info1);
                     let is_dir2 = this.is_directory(info2);
 
-                    if is_dir1 && !is_dir2 {
-                        return gtk::Ordering::Smaller;
+                    if is_dir1 != is_dir2 {
+                        return if is_dir1 == directories_first {
+                            gtk::Ordering::Smaller
+                        } else {
+                            gtk::Ordering::Larger
+                        };
                     }
+                }
 
-                    if is_dir2 && !is_dir1 {
-                        return gtk::Ordering::Larger;
-                    }
+                let custom_sort_func = this.imp().custom_sort_func.borrow().clone();
+                if let Some(CustomSortFunc(func)) = custom_sort_func {
+                    let ordering = func(info1, info2);
+                    let ordering = if this.reversed() {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    };
+                    return ordering.into();
                 }
 
                 let mode = *this.imp().sort_mode.borrow();
                 match mode {
                     SortMode::DisplayName => this.sort_by_name(info1, info2),
                     SortMode::ModificationTime => this.sort_by_modification_time(info1, info2),
+                    SortMode::Size => this.sort_by_size(info1, info2),
                 }
             }
         ));
@@ -768,10 +2334,10 @@ impl DirView {
                 let search_term = this.imp().search_term.borrow();
 
                 if search_term.is_some()
-                    && !info
-                        .display_name()
+                    && !this
+                        .name_keys(info)
+                        .lowercase
                         .trim()
-                        .to_lowercase()
                         .starts_with(search_term.as_ref().unwrap())
                 {
                     return false;
@@ -781,14 +2347,27 @@ impl DirView {
                     return false;
                 }
 
+                // Keep folders so users can still browse
+                if !this.is_directory(info)
+                    && !(this.in_modified_range(info)
+                        && this.in_size_range(info)
+                        && this.matches_glob(info))
+                {
+                    return false;
+                }
+
+                let custom_filter_func = this.imp().custom_filter_func.borrow().clone();
+                if let Some(CustomFilterFunc(func)) = custom_filter_func {
+                    if !func(info) {
+                        return false;
+                    }
+                }
+
                 if this.imp().show_hidden.get() {
                     return true;
                 }
 
-                if info.display_name().starts_with('.') {
-                    return false;
-                }
-                return true;
+                !util::is_hidden(info) && !util::is_backup(info)
             }
         ));
         self.imp().filtered_list.set_filter(Some(&custom_filter));
@@ -802,96 +2381,1477 @@ impl DirView {
             );
             self.set_icon_size(96);
             self.set_thumbnail_mode(ThumbnailMode::Local);
+            self.set_directories_first_mode(DirectoriesFirstMode::Always);
             return;
         }
 
         let settings = gio::Settings::new("mobi.phosh.FileSelector");
         settings.bind("icon-size", self, "icon-size").build();
+        settings.bind("view-mode", self, "view-mode").build();
         settings
             .bind("thumbnail-mode", self, "thumbnail-mode")
             .build();
+        settings
+            .bind("directories-first-mode", self, "directories-first-mode")
+            .build();
+        settings.bind("show-hidden", self, "show-hidden").build();
+        settings.bind("show-details", self, "show-details").build();
+        settings.bind("item-details", self, "item-details").build();
     }
 
-    pub fn set_sorting(&self, sort_mode: SortMode, reversed: bool) {
-        glib::g_debug!(
-            LOG_DOMAIN,
-            "Sorting mode {sort_mode:#?}, reversed: {reversed:#?}"
+    fn add_toast(&self, toast: adw::Toast) {
+        let Some(file_selector) = self.root().and_downcast::<FileSelector>() else {
+            return;
+        };
+
+        file_selector.show_toast(toast);
+    }
+
+    fn show_toast(&self, title: &str) {
+        self.add_toast(adw::Toast::new(title));
+    }
+
+    /// Asks the user for a name and creates a new folder with that name
+    /// in the current folder.
+    pub fn create_folder(&self) {
+        let Some(folder) = self.folder() else {
+            return;
+        };
+
+        let entry = gtk::Entry::builder().activates_default(true).build();
+        let dialog = adw::AlertDialog::builder()
+            .heading(gettextrs::gettext("New Folder"))
+            .extra_child(&entry)
+            .close_response("cancel")
+            .default_response("create")
+            .build();
+
+        dialog.add_response("cancel", &gettextrs::gettext("Cancel"));
+        dialog.add_response("create", &gettextrs::gettext("C_reate"));
+        dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+        dialog.set_response_enabled("create", false);
+
+        entry.connect_changed(glib::clone!(
+            #[weak]
+            dialog,
+            move |entry| {
+                let name = entry.text();
+                let valid = !name.is_empty() && name != "." && name != ".." && !name.contains('/');
+                dialog.set_response_enabled("create", valid);
+            }
+        ));
+
+        dialog.choose(
+            Some(self),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[weak]
+                entry,
+                move |response| {
+                    if response == "create" {
+                        this.make_folder(&folder.child(entry.text().as_str()));
+                    }
+                }
+            ),
         );
+    }
 
-        *self.imp().sort_mode.borrow_mut() = sort_mode;
-        self.imp().reversed.replace(reversed);
+    fn make_folder(&self, file: &gio::File) {
+        glib::g_debug!(LOG_DOMAIN, "Creating folder {}", file.uri());
 
-        self.notify_sort_mode();
-        self.notify_reversed();
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[strong]
+            file,
+            async move {
+                if let Err(err) = file.make_directory_future(glib::Priority::DEFAULT).await {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to create {}: {err}", file.uri());
+                    let msg = gettextrs::gettext("Failed to create folder “{}”").replacen(
+                        "{}",
+                        &Self::file_name(&file),
+                        1,
+                    );
+                    this.show_toast(&msg);
+                }
+            }
+        ));
+    }
 
-        // Resort
-        let sorter = self.imp().sorted_list.sorter().unwrap();
-        let change = gtk::SorterChange::Inverted;
-        sorter.emit_by_name::<()>("changed", &[&change]);
+    fn file_name(file: &gio::File) -> String {
+        file.basename()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
     }
 
-    fn select_item_real(&self, item: &gio::File) {
-        let imp = self.imp();
-        let uri = item.uri();
-        glib::g_debug!(LOG_DOMAIN, "Selecting {uri}");
+    /// The file info of the currently selected file, if any
+    pub fn selected_info(&self) -> Option<gio::FileInfo> {
+        self.imp()
+            .single_selection
+            .selected_item()
+            .and_downcast::<gio::FileInfo>()
+    }
 
-        if let Some(select_item_id) = imp.select_item_id.replace(None) {
-            imp.directory_list.disconnect(select_item_id);
-        }
+    fn selected_file(&self) -> Option<gio::File> {
+        self.imp()
+            .single_selection
+            .selected_item()
+            .and_downcast::<gio::FileInfo>()?
+            .attribute_object("standard::file")
+            .and_downcast::<gio::File>()
+    }
 
-        if let Some(model) = imp.single_selection.model() {
-            let n_items = model.n_items();
+    /// Notes that `file` is about to be removed from the folder by us
+    /// (e.g. trashed or renamed) so its removal isn't reported as
+    /// unexpected.
+    pub(crate) fn add_own_removal(&self, file: &gio::File) {
+        self.imp().own_removals.borrow_mut().push(file.clone());
+    }
 
-            if n_items == 0 {
-                return;
-            }
+    /// Forgets about `file` being removed by us. Returns whether it was.
+    pub(crate) fn take_own_removal(&self, file: &gio::File) -> bool {
+        let mut own_removals = self.imp().own_removals.borrow_mut();
+        let Some(pos) = own_removals.iter().position(|f| f.equal(file)) else {
+            return false;
+        };
+        own_removals.swap_remove(pos);
+        true
+    }
+
+    /// Forgets about `file` being removed by us once the folder had time
+    /// to pick up the finished operation.
+    pub(crate) fn finish_own_removal(&self, file: &gio::File) {
+        glib::timeout_add_local_once(
+            std::time::Duration::from_millis(OWN_REMOVAL_GRACE_MS),
+            glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[strong]
+                file,
+                move || {
+                    this.take_own_removal(&file);
+                }
+            ),
+        );
+    }
 
-            if let Some(name) = item.basename() {
-                for n in 0..n_items - 1 {
-                    let s = model.item(n);
+    /// Moves the currently selected file to the trash.
+    pub fn trash_selected(&self) {
+        if let Some(file) = self.selected_file() {
+            self.trash_file(&file);
+        }
+    }
 
-                    if let Some(info) = s {
-                        if info.downcast_ref::<gio::FileInfo>().unwrap().name() == name {
-                            glib::g_debug!(LOG_DOMAIN, "Found {name:?}, selecting");
-                            imp.grid_view
-                                .scroll_to(n, gtk::ListScrollFlags::SELECT, None);
-                            return;
-                        }
+    /// Moves `file` to the trash. If the file system doesn't support
+    /// trashing, the user is asked whether to delete it permanently.
+    pub fn trash_file(&self, file: &gio::File) {
+        let uri = file.uri();
+        glib::g_debug!(LOG_DOMAIN, "Trashing {uri}");
+        self.add_own_removal(file);
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[strong]
+            file,
+            async move {
+                let name = Self::file_name(&file);
+
+                match file.trash_future(glib::Priority::DEFAULT).await {
+                    Ok(()) => {
+                        this.finish_own_removal(&file);
+                        let title =
+                            gettextrs::gettext("“{}” moved to trash").replacen("{}", &name, 1);
+                        this.push_undo(UndoOp::Trash(file), &title);
+                    }
+                    Err(err) if err.matches(gio::IOErrorEnum::NotSupported) => {
+                        this.take_own_removal(&file);
+                        this.confirm_delete(&file);
+                    }
+                    Err(err) => {
+                        this.take_own_removal(&file);
+                        glib::g_warning!(LOG_DOMAIN, "Failed to trash {uri}: {err}");
+                        let msg = gettextrs::gettext("Failed to move “{}” to trash")
+                            .replacen("{}", &name, 1);
+                        this.show_toast(&msg);
                     }
                 }
-                glib::g_warning!(LOG_DOMAIN, "Couldn't find {name:?} in folder");
             }
-        } else {
-            glib::g_warning!(LOG_DOMAIN, "Couldn't get model");
-        }
+        ));
     }
 
-    pub fn select_item(&self, item: &gio::File) {
-        let imp = self.imp();
-
-        if imp.display_mode.get() == DisplayMode::Loading {
-            glib::g_debug!(LOG_DOMAIN, "Folder content still Loading");
+    fn confirm_delete(&self, file: &gio::File) {
+        let name = Self::file_name(file);
+        let body =
+            gettextrs::gettext("The file system doesn't support a trash. Permanently delete “{}”?")
+                .replacen("{}", &name, 1);
+
+        let dialog = adw::AlertDialog::builder()
+            .heading(gettextrs::gettext("Delete Permanently?"))
+            .body(&body)
+            .close_response("cancel")
+            .default_response("cancel")
+            .build();
 
-            if let Some(select_item_id) = imp.select_item_id.replace(None) {
-                imp.directory_list.disconnect(select_item_id);
-            }
+        dialog.add_response("cancel", &gettextrs::gettext("Cancel"));
+        dialog.add_response("delete", &gettextrs::gettext("_Delete"));
+        dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
 
-            let select_item_id = imp.directory_list.connect_loading_notify(glib::clone!(
+        dialog.choose(
+            Some(self),
+            None::<&gio::Cancellable>,
+            glib::clone!(
                 #[weak(rename_to = this)]
                 self,
-                #[strong(rename_to = toselect)]
-                item,
-                move |dirlist| {
-                    if dirlist.is_loading() {
-                        return;
+                #[strong]
+                file,
+                move |response| {
+                    if response == "delete" {
+                        this.delete_file(&file);
                     }
-                    glib::g_debug!(LOG_DOMAIN, "Loading finished");
-                    this.select_item_real(&toselect);
                 }
-            ));
-            imp.select_item_id.replace(Some(select_item_id));
-        } else {
-            self.select_item_real(item);
-        }
+            ),
+        );
+    }
+
+    fn delete_file(&self, file: &gio::File) {
+        self.add_own_removal(file);
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[strong]
+            file,
+            async move {
+                let name = Self::file_name(&file);
+
+                let msg = match file.delete_future(glib::Priority::DEFAULT).await {
+                    Ok(()) => {
+                        this.finish_own_removal(&file);
+                        gettextrs::gettext("“{}” deleted")
+                    }
+                    Err(err) => {
+                        this.take_own_removal(&file);
+                        glib::g_warning!(LOG_DOMAIN, "Failed to delete {}: {err}", file.uri());
+                        gettextrs::gettext("Failed to delete “{}”")
+                    }
+                }
+                .replacen("{}", &name, 1);
+                this.show_toast(&msg);
+            }
+        ));
+    }
+
+    // Looks up the most recently trashed item that originated from `file`
+    async fn find_in_trash(file: &gio::File) -> Option<gio::File> {
+        let orig_path = file.path()?;
+        let trash = gio::File::for_uri("trash:///");
+        let enumerator = trash
+            .enumerate_children_future(
+                "standard::name,trash::orig-path,trash::deletion-date",
+                gio::FileQueryInfoFlags::NONE,
+                glib::Priority::DEFAULT,
+            )
+            .await
+            .ok()?;
+
+        let mut found: Option<gio::FileInfo> = None;
+        loop {
+            let infos = enumerator
+                .next_files_future(64, glib::Priority::DEFAULT)
+                .await
+                .ok()?;
+            if infos.is_empty() {
+                break;
+            }
+
+            for info in infos {
+                if info.attribute_byte_string("trash::orig-path").as_deref() != orig_path.to_str() {
+                    continue;
+                }
+
+                if found
+                    .as_ref()
+                    .is_none_or(|f| f.deletion_date() < info.deletion_date())
+                {
+                    found = Some(info);
+                }
+            }
+        }
+
+        found.map(|info| trash.child(info.name()))
+    }
+
+    /// Remembers `op` so it can be undone and shows a toast with `title`
+    /// and an undo button. The operation expires with the toast.
+    pub(crate) fn push_undo(&self, op: UndoOp, title: &str) {
+        let imp = self.imp();
+        let id = imp.undo_serial.get() + 1;
+        imp.undo_serial.set(id);
+
+        let toast = adw::Toast::builder()
+            .title(title)
+            .use_markup(false)
+            .button_label(gettextrs::gettext("_Undo"))
+            .build();
+        toast.connect_button_clicked(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_| {
+                if let Some(entry) = this.take_undo_entry(id) {
+                    this.undo_op(entry.op);
+                }
+            }
+        ));
+        toast.connect_dismissed(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_| {
+                this.take_undo_entry(id);
+            }
+        ));
+
+        let evicted = {
+            let mut stack = imp.undo_stack.borrow_mut();
+            stack.push(UndoEntry {
+                id,
+                op,
+                toast: toast.clone(),
+            });
+            (stack.len() > UNDO_STACK_DEPTH).then(|| stack.remove(0))
+        };
+        // Its undo button would do nothing anymore
+        if let Some(entry) = evicted {
+            entry.toast.dismiss();
+        }
+        self.add_toast(toast);
+    }
+
+    fn take_undo_entry(&self, id: u64) -> Option<UndoEntry> {
+        let mut stack = self.imp().undo_stack.borrow_mut();
+        let pos = stack.iter().position(|entry| entry.id == id)?;
+        Some(stack.remove(pos))
+    }
+
+    /// Undoes the most recent file operation that didn't expire yet.
+    pub fn undo(&self) {
+        let Some(entry) = self.imp().undo_stack.borrow_mut().pop() else {
+            return;
+        };
+
+        entry.toast.dismiss();
+        self.undo_op(entry.op);
+    }
+
+    fn undo_op(&self, op: UndoOp) {
+        match op {
+            UndoOp::Trash(file) => self.restore_from_trash(&file),
+            UndoOp::Move(moved) => self.move_back(moved),
+            UndoOp::Rename { file, old_name } => self.rename_back(file, old_name),
+        }
+    }
+
+    fn move_back(&self, moved: Vec<(gio::File, gio::File)>) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                for (orig, dest) in moved {
+                    let (future, _progress) = dest.move_future(
+                        &orig,
+                        gio::FileCopyFlags::NOFOLLOW_SYMLINKS,
+                        glib::Priority::DEFAULT,
+                    );
+                    if let Err(err) = future.await {
+                        glib::g_warning!(LOG_DOMAIN, "Failed to move back {}: {err}", dest.uri());
+                        let msg = gettextrs::gettext("Failed to move “{}” back").replacen(
+                            "{}",
+                            &Self::file_name(&dest),
+                            1,
+                        );
+                        this.show_toast(&msg);
+                    }
+                }
+            }
+        ));
+    }
+
+    /// Renames `file` from `old_name` to `new_name` such that the rename
+    /// can be undone and the renamed file gets selected. Returns the
+    /// renamed file.
+    pub(crate) async fn rename_file(
+        &self,
+        file: &gio::File,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<gio::File, glib::Error> {
+        glib::g_debug!(LOG_DOMAIN, "Renaming {} to {new_name:#?}", file.uri());
+        self.add_own_removal(file);
+
+        let new_file = match file
+            .set_display_name_future(new_name, glib::Priority::DEFAULT)
+            .await
+        {
+            Ok(new_file) => new_file,
+            Err(err) => {
+                self.take_own_removal(file);
+                return Err(err);
+            }
+        };
+
+        self.finish_own_removal(file);
+        // Keep the renamed item selected once the folder got updated
+        self.select_item_once_added(&new_file);
+
+        let title = gettextrs::gettext("Renamed to “{}”").replacen("{}", new_name, 1);
+        let op = UndoOp::Rename {
+            file: new_file.clone(),
+            old_name: old_name.to_string(),
+        };
+        self.push_undo(op, &title);
+
+        Ok(new_file)
+    }
+
+    fn rename_back(&self, file: gio::File, old_name: String) {
+        self.add_own_removal(&file);
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                match file
+                    .set_display_name_future(&old_name, glib::Priority::DEFAULT)
+                    .await
+                {
+                    Ok(old_file) => {
+                        this.finish_own_removal(&file);
+                        this.select_item_once_added(&old_file);
+                    }
+                    Err(err) => {
+                        this.take_own_removal(&file);
+                        glib::g_warning!(LOG_DOMAIN, "Failed to rename back {}: {err}", file.uri());
+                        let msg = gettextrs::gettext("Failed to rename back to “{}”")
+                            .replacen("{}", &old_name, 1);
+                        this.show_toast(&msg);
+                    }
+                }
+            }
+        ));
+    }
+
+    fn restore_from_trash(&self, file: &gio::File) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[strong]
+            file,
+            async move {
+                let name = Self::file_name(&file);
+                let Some(trashed) = Self::find_in_trash(&file).await else {
+                    let msg = gettextrs::gettext("Failed to restore “{}”").replacen("{}", &name, 1);
+                    this.show_toast(&msg);
+                    return;
+                };
+
+                let (future, _progress) = trashed.move_future(
+                    &file,
+                    gio::FileCopyFlags::NOFOLLOW_SYMLINKS,
+                    glib::Priority::DEFAULT,
+                );
+                if let Err(err) = future.await {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to restore {}: {err}", file.uri());
+                    let msg = gettextrs::gettext("Failed to restore “{}”").replacen("{}", &name, 1);
+                    this.show_toast(&msg);
+                }
+            }
+        ));
+    }
+
+    fn setup_drop_target(&self) {
+        let drop_target = gtk::DropTarget::new(
+            gdk::FileList::static_type(),
+            gdk::DragAction::COPY | gdk::DragAction::MOVE,
+        );
+
+        drop_target.connect_drop(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[upgrade_or]
+            false,
+            move |target, value, _, _| {
+                let Ok(file_list) = value.get::<gdk::FileList>() else {
+                    return false;
+                };
+
+                let move_files = target
+                    .current_event_state()
+                    .contains(gdk::ModifierType::SHIFT_MASK);
+                this.transfer_files(file_list.files(), move_files);
+                true
+            }
+        ));
+
+        self.add_controller(drop_target.clone());
+        *self.imp().drop_target.borrow_mut() = Some(drop_target);
+    }
+
+    // Only accept drops when the current folder is writable
+    fn update_drop_target(&self) {
+        let Some(drop_target) = self.imp().drop_target.borrow().clone() else {
+            return;
+        };
+
+        drop_target.set_actions(gdk::DragAction::empty());
+        self.imp().folder_writable.set(false);
+        self.update_can_paste();
+        let Some(folder) = self.folder() else {
+            return;
+        };
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let can_write = match folder
+                    .query_info_future(
+                        gio::FILE_ATTRIBUTE_ACCESS_CAN_WRITE,
+                        gio::FileQueryInfoFlags::NONE,
+                        glib::Priority::DEFAULT,
+                    )
+                    .await
+                {
+                    Ok(info) => info.boolean(gio::FILE_ATTRIBUTE_ACCESS_CAN_WRITE),
+                    Err(err) => {
+                        glib::g_debug!(LOG_DOMAIN, "Failed to query folder access: {err}");
+                        false
+                    }
+                };
+
+                // Folder changed meanwhile
+                if this.folder().is_none_or(|f| !f.equal(&folder)) {
+                    return;
+                }
+
+                let actions = if can_write {
+                    gdk::DragAction::COPY | gdk::DragAction::MOVE
+                } else {
+                    gdk::DragAction::empty()
+                };
+                drop_target.set_actions(actions);
+                this.imp().folder_writable.set(can_write);
+                this.update_can_paste();
+            }
+        ));
+    }
+
+    fn update_can_paste(&self) {
+        let formats = self.clipboard().formats();
+        // Other apps offer the files as URI list
+        let has_files = formats.contains_type(gdk::FileList::static_type())
+            || formats.contain_mime_type("text/uri-list")
+            || formats.contain_mime_type(GNOME_COPIED_FILES);
+        let can_paste = has_files && self.imp().folder_writable.get();
+
+        if self.imp().can_paste.replace(can_paste) != can_paste {
+            self.notify_can_paste();
+        }
+    }
+
+    /// Puts the currently selected file on the clipboard so it can be
+    /// pasted into another folder or application.
+    pub fn copy_selected(&self) {
+        if let Some(file) = self.selected_file() {
+            self.copy_files(&[file], false);
+        }
+    }
+
+    /// Like [`Self::copy_selected`] but the file gets moved on paste.
+    pub fn cut_selected(&self) {
+        if let Some(file) = self.selected_file() {
+            self.copy_files(&[file], true);
+        }
+    }
+
+    /// Puts `files` on the clipboard. If `cut` is set, pasting moves
+    /// them instead of copying.
+    pub fn copy_files(&self, files: &[gio::File], cut: bool) {
+        glib::g_debug!(
+            LOG_DOMAIN,
+            "Putting {} file(s) on clipboard, cut: {cut}",
+            files.len()
+        );
+
+        // Offer GNOME's target too so e.g. Files knows whether to move
+        let content = copied_files_content(files, cut);
+        let provider = gdk::ContentProvider::new_union(&[
+            gdk::ContentProvider::for_value(&gdk::FileList::from_array(files).to_value()),
+            gdk::ContentProvider::for_bytes(
+                GNOME_COPIED_FILES,
+                &glib::Bytes::from_owned(content.into_bytes()),
+            ),
+        ]);
+
+        if let Err(err) = self.clipboard().set_content(Some(&provider)) {
+            glib::g_warning!(LOG_DOMAIN, "Failed to set clipboard content: {err}");
+        }
+    }
+
+    // Reads the cut or copied files in GNOME's format
+    async fn read_copied_files(&self) -> Result<Option<(Vec<gio::File>, bool)>, glib::Error> {
+        let (stream, _) = self
+            .clipboard()
+            .read_future(&[GNOME_COPIED_FILES], glib::Priority::DEFAULT)
+            .await?;
+
+        let output = gio::MemoryOutputStream::new_resizable();
+        output
+            .splice_future(
+                &stream,
+                gio::OutputStreamSpliceFlags::CLOSE_SOURCE
+                    | gio::OutputStreamSpliceFlags::CLOSE_TARGET,
+                glib::Priority::DEFAULT,
+            )
+            .await?;
+
+        let bytes = output.steal_as_bytes();
+        Ok(parse_copied_files(&String::from_utf8_lossy(&bytes)))
+    }
+
+    // Reads the files on the clipboard, these are always copied
+    async fn read_file_list(&self) -> Result<Option<(Vec<gio::File>, bool)>, glib::Error> {
+        let value = self
+            .clipboard()
+            .read_value_future(gdk::FileList::static_type(), glib::Priority::DEFAULT)
+            .await?;
+
+        Ok(value
+            .get::<gdk::FileList>()
+            .ok()
+            .map(|file_list| (file_list.files(), false)))
+    }
+
+    /// Copies the files on the clipboard into the current folder or
+    /// moves them there if they were cut.
+    pub fn paste(&self) {
+        if !self.can_paste() {
+            return;
+        }
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let clipboard = this.clipboard();
+                let result = if clipboard.formats().contain_mime_type(GNOME_COPIED_FILES) {
+                    this.read_copied_files().await
+                } else {
+                    this.read_file_list().await
+                };
+
+                match result {
+                    Ok(Some((files, cut))) => {
+                        this.transfer_files(files, cut);
+                        // Cut files are gone from where the clipboard points to
+                        if cut {
+                            let _ = clipboard.set_content(None::<&gdk::ContentProvider>);
+                        }
+                    }
+                    Ok(None) => {
+                        glib::g_warning!(LOG_DOMAIN, "Unexpected clipboard content")
+                    }
+                    Err(err) => {
+                        glib::g_warning!(LOG_DOMAIN, "Failed to read clipboard: {err}");
+                        this.show_toast(&gettextrs::gettext("Failed to paste files"));
+                    }
+                }
+            }
+        ));
+    }
+
+    fn on_transfer_done(
+        &self,
+        n_files: usize,
+        n_failed: usize,
+        move_files: bool,
+        moved: Vec<(gio::File, gio::File)>,
+        cancellable: gio::Cancellable,
+    ) {
+        let imp = self.imp();
+        // Only hide the progress once all transfers are done
+        let all_done = {
+            let mut cancellables = imp.transfer_cancellables.borrow_mut();
+            cancellables.retain(|c| c != &cancellable);
+            cancellables.is_empty()
+        };
+        if all_done {
+            imp.transfer_box.set_visible(false);
+        }
+
+        let n_done = n_files - n_failed;
+        let msg = if cancellable.is_cancelled() {
+            gettextrs::gettext("Transfer cancelled")
+        } else if n_failed > 0 {
+            gettextrs::ngettext(
+                "Failed to transfer {} file",
+                "Failed to transfer {} files",
+                n_failed as u32,
+            )
+            .replacen("{}", &n_failed.to_string(), 1)
+        } else if move_files {
+            gettextrs::ngettext("Moved {} file", "Moved {} files", n_done as u32).replacen(
+                "{}",
+                &n_done.to_string(),
+                1,
+            )
+        } else {
+            gettextrs::ngettext("Copied {} file", "Copied {} files", n_done as u32).replacen(
+                "{}",
+                &n_done.to_string(),
+                1,
+            )
+        };
+
+        if moved.is_empty() {
+            self.show_toast(&msg);
+        } else {
+            self.push_undo(UndoOp::Move(moved), &msg);
+        }
+    }
+
+    // Copies the folder `src` with all its content to `dest` as GIO doesn't
+    // recurse into folders. With `move_files` the sources get removed once
+    // they're copied.
+    async fn transfer_folder(
+        src: gio::File,
+        dest: gio::File,
+        move_files: bool,
+        cancellable: gio::Cancellable,
+    ) -> Result<(), glib::Error> {
+        let mut dirs = vec![(src, dest)];
+        let mut copied_dirs = Vec::new();
+
+        while let Some((src, dest)) = dirs.pop() {
+            cancellable.set_error_if_cancelled()?;
+            dest.make_directory_future(glib::Priority::DEFAULT).await?;
+
+            let enumerator = src
+                .enumerate_children_future(
+                    "standard::name,standard::type",
+                    gio::FileQueryInfoFlags::NOFOLLOW_SYMLINKS,
+                    glib::Priority::DEFAULT,
+                )
+                .await?;
+            loop {
+                let infos = enumerator
+                    .next_files_future(64, glib::Priority::DEFAULT)
+                    .await?;
+                if infos.is_empty() {
+                    break;
+                }
+
+                for info in infos {
+                    cancellable.set_error_if_cancelled()?;
+
+                    let child = src.child(info.name());
+                    let dest_child = dest.child(info.name());
+                    if info.file_type() == gio::FileType::Directory {
+                        dirs.push((child, dest_child));
+                        continue;
+                    }
+
+                    let (copy, _) = child.copy_future(
+                        &dest_child,
+                        gio::FileCopyFlags::NOFOLLOW_SYMLINKS,
+                        glib::Priority::DEFAULT,
+                    );
+                    copy.await?;
+                    if move_files {
+                        child.delete_future(glib::Priority::DEFAULT).await?;
+                    }
+                }
+            }
+            copied_dirs.push(src);
+        }
+
+        // Sub folders come after their parents
+        if move_files {
+            for dir in copied_dirs.into_iter().rev() {
+                dir.delete_future(glib::Priority::DEFAULT).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies (or moves) `files` into the current folder.
+    pub fn transfer_files(&self, files: Vec<gio::File>, move_files: bool) {
+        let Some(folder) = self.folder() else {
+            return;
+        };
+
+        // Nothing to do for files that are already in this folder
+        let files: Vec<gio::File> = files
+            .into_iter()
+            .filter(|file| file.parent().is_none_or(|parent| !parent.equal(&folder)))
+            .collect();
+        let n_files = files.len();
+        if n_files == 0 {
+            return;
+        }
+
+        let imp = self.imp();
+        // The progress shown is the one of the most recent transfer
+        let cancellable = gio::Cancellable::new();
+        imp.transfer_cancellables
+            .borrow_mut()
+            .push(cancellable.clone());
+        imp.progress_bar.set_fraction(0.0);
+        imp.transfer_label.set_label("");
+        imp.transfer_size_label.set_label("");
+        imp.transfer_box.set_visible(true);
+
+        let pending = Rc::new(Cell::new(n_files));
+        let failed = Rc::new(Cell::new(0));
+        let moved = Rc::new(RefCell::new(Vec::new()));
+        let progress = Rc::new(RefCell::new(vec![(0_i64, 0_i64); n_files]));
+
+        for (i, file) in files.into_iter().enumerate() {
+            let uri = file.uri();
+            let dest = folder.child(file.basename().unwrap_or_default());
+            glib::g_debug!(LOG_DOMAIN, "Transferring {uri} to {}", dest.uri());
+
+            let label = if move_files {
+                gettextrs::gettext("Moving “{}”")
+            } else {
+                gettextrs::gettext("Copying “{}”")
+            }
+            .replacen("{}", &Self::file_name(&file), 1);
+
+            let progress_cb = Box::new(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[strong]
+                progress,
+                #[strong]
+                cancellable,
+                move |current: i64, total: i64| {
+                    let imp = this.imp();
+                    if imp.transfer_cancellables.borrow().last() != Some(&cancellable) {
+                        return;
+                    }
+
+                    let mut progress = progress.borrow_mut();
+                    progress[i] = (current, total);
+
+                    let (current, total) = progress
+                        .iter()
+                        .fold((0, 0), |(c, t), (current, total)| (c + current, t + total));
+                    if total > 0 {
+                        imp.progress_bar.set_fraction(current as f64 / total as f64);
+                    }
+
+                    let size = gettextrs::gettext("{current} of {total}")
+                        .replacen("{current}", &util::format_size(current as u64), 1)
+                        .replacen("{total}", &util::format_size(total as u64), 1);
+                    imp.transfer_label.set_label(&label);
+                    imp.transfer_size_label.set_label(&size);
+                }
+            ));
+
+            let done_cb = glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[strong]
+                pending,
+                #[strong]
+                failed,
+                #[strong]
+                moved,
+                #[strong]
+                file,
+                #[strong]
+                dest,
+                #[strong]
+                cancellable,
+                move |result: Result<(), glib::Error>| {
+                    match result {
+                        Ok(()) if move_files => moved.borrow_mut().push((file, dest)),
+                        Ok(()) => {}
+                        Err(err) => {
+                            if !err.matches(gio::IOErrorEnum::Cancelled) {
+                                glib::g_warning!(LOG_DOMAIN, "Failed to transfer {uri}: {err}");
+                            }
+                            failed.set(failed.get() + 1);
+                        }
+                    }
+
+                    pending.set(pending.get() - 1);
+                    if pending.get() == 0 {
+                        this.on_transfer_done(
+                            n_files,
+                            failed.get(),
+                            move_files,
+                            moved.take(),
+                            cancellable,
+                        );
+                    }
+                }
+            );
+
+            // Folders fail with `WouldRecurse`, walk them ourselves
+            let (src, target, folder_cancellable) =
+                (file.clone(), dest.clone(), cancellable.clone());
+            let transferred_cb = move |result: Result<(), glib::Error>| match result {
+                Err(err) if err.matches(gio::IOErrorEnum::WouldRecurse) => {
+                    glib::spawn_future_local(async move {
+                        done_cb(
+                            Self::transfer_folder(src, target, move_files, folder_cancellable)
+                                .await,
+                        );
+                    });
+                }
+                result => done_cb(result),
+            };
+
+            if move_files {
+                file.move_async(
+                    &dest,
+                    gio::FileCopyFlags::NONE,
+                    glib::Priority::DEFAULT,
+                    Some(&cancellable),
+                    Some(progress_cb),
+                    transferred_cb,
+                );
+            } else {
+                file.copy_async(
+                    &dest,
+                    gio::FileCopyFlags::NONE,
+                    glib::Priority::DEFAULT,
+                    Some(&cancellable),
+                    Some(progress_cb),
+                    transferred_cb,
+                );
+            }
+        }
+    }
+
+    pub fn set_sorting(&self, sort_mode: SortMode, reversed: bool) {
+        glib::g_debug!(
+            LOG_DOMAIN,
+            "Sorting mode {sort_mode:#?}, reversed: {reversed:#?}"
+        );
+
+        let old_mode = self.imp().sort_mode.replace(sort_mode);
+        let old_reversed = self.imp().reversed.replace(reversed);
+
+        self.notify_sort_mode();
+        self.notify_reversed();
+
+        // Resort. Neither folder grouping nor tie breaking invert with the
+        // sort direction so it's never a plain `Inverted` change.
+        if old_mode != sort_mode || old_reversed != reversed {
+            self.resort();
+        }
+    }
+
+    /// Selects all items currently shown. Items hidden by a filter or the
+    /// search term aren't selected. Does nothing unless the selection
+    /// model supports multiple selected items.
+    pub fn select_all(&self) {
+        if !self.imp().single_selection.select_all() {
+            glib::g_debug!(
+                LOG_DOMAIN,
+                "Selection model doesn't support selecting all items"
+            );
+        }
+    }
+
+    /// Unselects all items. Does nothing unless the selection model
+    /// supports multiple selected items.
+    pub fn unselect_all(&self) {
+        if !self.imp().single_selection.unselect_all() {
+            glib::g_debug!(
+                LOG_DOMAIN,
+                "Selection model doesn't support unselecting all items"
+            );
+        }
+    }
+
+    /// Sorts the folder's contents with `func` instead of the built-in
+    /// sort modes. Directories are still grouped according to
+    /// `directories-first-mode` and `reversed` inverts the order.
+    pub fn set_custom_sorter(
+        &self,
+        func: impl Fn(&gio::FileInfo, &gio::FileInfo) -> Ordering + 'static,
+    ) {
+        self.imp()
+            .custom_sort_func
+            .replace(Some(CustomSortFunc(Rc::new(func))));
+        self.resort();
+    }
+
+    /// Removes a sort function set via [`Self::set_custom_sorter`] so
+    /// the built-in sort modes are used again.
+    pub fn clear_custom_sorter(&self) {
+        if self.imp().custom_sort_func.take().is_some() {
+            self.resort();
+        }
+    }
+
+    /// Only shows items for which `func` returns `true`. This is applied
+    /// in addition to the built-in filters like `show-hidden`, the search
+    /// term or the type filter.
+    pub fn set_custom_filter(&self, func: impl Fn(&gio::FileInfo) -> bool + 'static) {
+        self.imp()
+            .custom_filter_func
+            .replace(Some(CustomFilterFunc(Rc::new(func))));
+        self.refilter();
+    }
+
+    /// Removes a filter function set via [`Self::set_custom_filter`].
+    pub fn clear_custom_filter(&self) {
+        if self.imp().custom_filter_func.take().is_some() {
+            self.refilter();
+        }
+    }
+
+    fn refilter(&self) {
+        if let Some(filter) = self.imp().filtered_list.filter() {
+            filter.emit_by_name::<()>("changed", &[&gtk::FilterChange::Different]);
+        }
+    }
+
+    fn resort(&self) {
+        if let Some(sorter) = self.imp().sorted_list.sorter() {
+            sorter.emit_by_name::<()>("changed", &[&gtk::SorterChange::Different]);
+        }
+    }
+
+    fn item_position(&self, item: &gio::File) -> Option<u32> {
+        let model = self.imp().single_selection.model()?;
+        let name = item.basename()?;
+
+        (0..model.n_items()).find(|n| {
+            model
+                .item(*n)
+                .and_downcast::<gio::FileInfo>()
+                .is_some_and(|info| info.name() == name)
+        })
+    }
+
+    fn select_item_real(&self, item: &gio::File) {
+        let imp = self.imp();
+        let uri = item.uri();
+        glib::g_debug!(LOG_DOMAIN, "Selecting {uri}");
+
+        if let Some(select_item_id) = imp.select_item_id.replace(None) {
+            imp.directory_list.disconnect(select_item_id);
+        }
+
+        match self.item_position(item) {
+            Some(pos) => {
+                glib::g_debug!(LOG_DOMAIN, "Found {uri}, selecting");
+                self.scroll_to(
+                    pos,
+                    gtk::ListScrollFlags::SELECT | gtk::ListScrollFlags::FOCUS,
+                );
+            }
+            None => {
+                let is_hidden = item.basename().is_some_and(|name| {
+                    let name = name.to_string_lossy();
+                    name.starts_with('.') || name.ends_with('~')
+                });
+
+                if is_hidden && !self.show_hidden() {
+                    glib::g_debug!(LOG_DOMAIN, "Not selecting hidden {uri}");
+                } else {
+                    glib::g_warning!(LOG_DOMAIN, "Couldn't find {uri} in folder");
+                }
+            }
+        }
+    }
+
+    fn setup_letter_index(&self) {
+        let imp = self.imp();
+
+        for letter in 'A'..='Z' {
+            let button = gtk::Button::builder()
+                .label(letter.to_string())
+                .css_classes(["flat"])
+                .focus_on_click(false)
+                .build();
+            button.connect_clicked(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_| this.jump_to_letter(letter)
+            ));
+            imp.letter_index.append(&button);
+        }
+
+        for name in ["show-letter-index", "sort-mode", "display-mode"] {
+            self.connect_notify_local(Some(name), |this, _| this.update_letter_index());
+        }
+        self.update_letter_index();
+    }
+
+    // Only useful when sorted by name and there's enough to scroll through
+    fn update_letter_index(&self) {
+        let imp = self.imp();
+        let visible = self.show_letter_index()
+            && self.sort_mode() == SortMode::DisplayName
+            && self.display_mode() == DisplayMode::Content
+            && imp.filtered_list.n_items() > LETTER_INDEX_MIN_ITEMS;
+
+        imp.letter_index.set_visible(visible);
+    }
+
+    fn jump_to_letter(&self, letter: char) {
+        let model = self.imp().single_selection.get();
+        let letter = letter.to_lowercase().to_string();
+
+        let pos = (0..model.n_items()).find(|pos| {
+            model
+                .item(*pos)
+                .and_downcast::<gio::FileInfo>()
+                .is_some_and(|info| self.name_keys(&info).lowercase.starts_with(&letter))
+        });
+
+        if let Some(pos) = pos {
+            self.scroll_to(pos, gtk::ListScrollFlags::FOCUS);
+        }
+    }
+
+    // Scroll the visible view to the item at `pos`
+    fn scroll_to(&self, pos: u32, flags: gtk::ListScrollFlags) {
+        let imp = self.imp();
+
+        match imp.view_mode.get() {
+            ViewMode::Grid => imp.grid_view.scroll_to(pos, flags, None),
+            ViewMode::List => {
+                imp.column_view
+                    .scroll_to(pos, None::<&gtk::ColumnViewColumn>, flags, None)
+            }
+        }
+    }
+
+    fn reset_type_ahead(&self) {
+        let imp = self.imp();
+
+        imp.type_ahead_prefix.borrow_mut().clear();
+        if let Some(source_id) = imp.type_ahead_timeout_id.take() {
+            source_id.remove();
+        }
+    }
+
+    // Move to the first item starting with the typed prefix
+    fn type_ahead_find(&self) {
+        let imp = self.imp();
+        let prefix = imp.type_ahead_prefix.borrow().clone();
+        let model = imp.single_selection.get();
+
+        let found = (0..model.n_items()).find_map(|pos| {
+            let info = model.item(pos).and_downcast::<gio::FileInfo>()?;
+            self.name_keys(&info)
+                .lowercase
+                .starts_with(&prefix)
+                .then_some((pos, info))
+        });
+        let Some((pos, info)) = found else {
+            return;
+        };
+
+        // Selecting a folder would enter it, so only focus these
+        let flags = if self.is_directory(&info) {
+            gtk::ListScrollFlags::FOCUS
+        } else {
+            gtk::ListScrollFlags::FOCUS | gtk::ListScrollFlags::SELECT
+        };
+        self.scroll_to(pos, flags);
+    }
+
+    /// Selects `item` as soon as it shows up in the folder (e.g. after
+    /// a rename). Selects it right away if it's already there.
+    pub fn select_item_once_added(&self, item: &gio::File) {
+        let imp = self.imp();
+
+        if let Some(select_item_id) = imp.select_item_id.replace(None) {
+            imp.directory_list.disconnect(select_item_id);
+        }
+
+        if self.item_position(item).is_some() {
+            self.select_item_real(item);
+            return;
+        }
+
+        let select_item_id = imp.directory_list.connect_items_changed(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[strong(rename_to = toselect)]
+            item,
+            move |_, _, _, added| {
+                if added == 0 || this.item_position(&toselect).is_none() {
+                    return;
+                }
+                this.select_item_real(&toselect);
+            }
+        ));
+        imp.select_item_id.replace(Some(select_item_id));
+    }
+
+    /// Selects `file` and scrolls it into view. If the folder is still
+    /// loading the file gets selected once loading finished.
+    ///
+    /// Files outside the current folder and hidden files while hidden
+    /// files aren't shown can't be selected.
+    pub fn select_file(&self, file: &gio::File) {
+        let in_folder = file
+            .parent()
+            .zip(self.folder())
+            .is_some_and(|(parent, folder)| parent.equal(&folder));
+
+        if !in_folder {
+            glib::g_warning!(LOG_DOMAIN, "{} isn't in the current folder", file.uri());
+            return;
+        }
+
+        self.select_item(file);
+    }
+
+    pub fn select_item(&self, item: &gio::File) {
+        let imp = self.imp();
+
+        // Don't go by the display mode as it lags behind during the loading grace period
+        if imp.directory_list.is_loading() {
+            glib::g_debug!(LOG_DOMAIN, "Folder content still Loading");
+
+            if let Some(select_item_id) = imp.select_item_id.replace(None) {
+                imp.directory_list.disconnect(select_item_id);
+            }
+
+            let select_item_id = imp.directory_list.connect_loading_notify(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[strong(rename_to = toselect)]
+                item,
+                move |dirlist| {
+                    if dirlist.is_loading() {
+                        return;
+                    }
+                    glib::g_debug!(LOG_DOMAIN, "Loading finished");
+                    this.select_item_real(&toselect);
+                }
+            ));
+            imp.select_item_id.replace(Some(select_item_id));
+        } else {
+            self.select_item_real(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_batch_max_delay() {
+        let mut batch = ThumbnailBatch::default();
+        let mut n_sent = 0;
+
+        // A bind every 100ms for 10s never leaves room for the debounce
+        // timeout to fire, still batches need to go out.
+        for i in 0..100 {
+            if batch.add(i * 100_000) {
+                batch.reset();
+                n_sent += 1;
+            }
+        }
+        assert_eq!(n_sent, 3);
+    }
+
+    // Counts how often the folder got (re)loaded
+    fn count_loads(dir_view: &DirView) -> Rc<Cell<u32>> {
+        let loads = Rc::new(Cell::new(0));
+
+        dir_view
+            .imp()
+            .directory_list
+            .connect_file_notify(glib::clone!(
+                #[strong]
+                loads,
+                move |list| {
+                    if list.file().is_some() {
+                        loads.set(loads.get() + 1);
+                    }
+                }
+            ));
+        loads
+    }
+
+    #[test]
+    fn test_reenter_folder() {
+        gtk::test_synced(|| {
+            crate::init::init();
+
+            let dir_view = glib::Object::new::<DirView>();
+            let loads = count_loads(&dir_view);
+
+            dir_view.set_folder(Some(&gio::File::for_path("/tmp")));
+            assert_eq!(loads.get(), 1);
+            dir_view.set_folder(Some(&gio::File::for_path("/tmp")));
+            assert_eq!(loads.get(), 1);
+
+            // Virtual locations get reloaded
+            dir_view.set_folder(Some(&gio::File::for_uri("recent:///")));
+            assert_eq!(loads.get(), 2);
+            dir_view.set_folder(Some(&gio::File::for_uri("recent:///")));
+            assert_eq!(loads.get(), 3);
+        });
+    }
+
+    #[test]
+    fn test_own_removals() {
+        gtk::test_synced(|| {
+            crate::init::init();
+
+            let dir_view = glib::Object::new::<DirView>();
+            let file = gio::File::for_path("/tmp/a");
+
+            assert!(!dir_view.take_own_removal(&file));
+            dir_view.add_own_removal(&file);
+            assert!(dir_view.take_own_removal(&gio::File::for_uri("file:///tmp/a")));
+            assert!(!dir_view.take_own_removal(&file));
+
+            // Entries go away once the operation finished
+            dir_view.add_own_removal(&file);
+            dir_view.finish_own_removal(&file);
+            let ctx = glib::MainContext::default();
+            while !dir_view.imp().own_removals.borrow().is_empty() {
+                ctx.iteration(true);
+            }
+            assert!(!dir_view.take_own_removal(&file));
+        });
+    }
+
+    #[test]
+    fn test_clear_folder() {
+        gtk::test_synced(|| {
+            crate::init::init();
+
+            let dir_view = glib::Object::new::<DirView>();
+            let changed = Rc::new(RefCell::new(Vec::new()));
+            dir_view.connect_closure(
+                "folder-changed",
+                false,
+                glib::closure_local!(
+                    #[strong]
+                    changed,
+                    move |_: DirView, uri: String| changed.borrow_mut().push(uri)
+                ),
+            );
+
+            dir_view.set_folder(Some(&gio::File::for_path("/tmp")));
+            assert_ne!(dir_view.display_mode(), DisplayMode::NoFolder);
+
+            dir_view.set_folder(None::<&gio::File>);
+            assert!(dir_view.folder().is_none());
+            assert!(dir_view.imp().directory_list.file().is_none());
+            assert_eq!(dir_view.imp().filtered_list.n_items(), 0);
+            assert_eq!(dir_view.display_mode(), DisplayMode::NoFolder);
+            assert_eq!(*changed.borrow(), ["file:///tmp", ""]);
+
+            // Clearing again is a no-op
+            dir_view.set_folder(None::<&gio::File>);
+            assert_eq!(changed.borrow().len(), 2);
+        });
+    }
+
+    fn file_info(name: &str, size: i64) -> gio::FileInfo {
+        let info = gio::FileInfo::new();
+        info.set_name(name);
+        info.set_display_name(name);
+        info.set_size(size);
+        info
+    }
+
+    fn tie_break_infos(info1: &gio::FileInfo, info2: &gio::FileInfo) -> Ordering {
+        let keys1 = NameKeys::new(&info1.display_name());
+        let keys2 = NameKeys::new(&info2.display_name());
+
+        tie_break(info1, &keys1, info2, &keys2)
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let a = file_info("a", 1);
+        let b = file_info("b", 1);
+        assert_eq!(tie_break_infos(&a, &b), Ordering::Less);
+        assert_eq!(tie_break_infos(&b, &a), Ordering::Greater);
+        assert_eq!(tie_break_infos(&a, &a), Ordering::Equal);
+
+        // Numbers in file names sort naturally
+        let file9 = file_info("file9", 1);
+        let file10 = file_info("file10", 1);
+        assert_eq!(tie_break_infos(&file9, &file10), Ordering::Less);
+    }
+
+    #[test]
+    fn test_name_keys() {
+        gtk::test_synced(|| {
+            crate::init::init();
+
+            let dir_view = glib::Object::new::<DirView>();
+            let arger = file_info("Ärger.TXT", 1);
+            let keys = dir_view.name_keys(&arger);
+            assert_eq!(keys.lowercase, "ärger.txt");
+            // Computed once per name
+            assert!(Rc::ptr_eq(
+                &keys,
+                &dir_view.name_keys(&file_info("Ärger.TXT", 2))
+            ));
+
+            // Cached keys don't outlive the folder
+            dir_view.set_folder(Some(&gio::File::for_path("/tmp")));
+            assert!(dir_view.imp().name_keys.borrow().is_empty());
+            dir_view.name_keys(&arger);
+            dir_view.set_folder(None::<&gio::File>);
+            assert!(dir_view.imp().name_keys.borrow().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_copied_files() {
+        let files = [
+            gio::File::for_uri("file:///tmp/a"),
+            gio::File::for_uri("file:///tmp/b%20c"),
+        ];
+
+        let content = copied_files_content(&files, true);
+        assert_eq!(content, "cut\nfile:///tmp/a\nfile:///tmp/b%20c");
+
+        let (parsed, cut) = parse_copied_files(&content).unwrap();
+        assert!(cut);
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed[1].equal(&files[1]));
+
+        let (parsed, cut) = parse_copied_files("copy\nfile:///tmp/a\n").unwrap();
+        assert!(!cut);
+        assert_eq!(parsed.len(), 1);
+
+        assert!(parse_copied_files("paste\nfile:///tmp/a").is_none());
+        assert!(parse_copied_files("").is_none());
+    }
+
+    #[test]
+    fn test_thumbnail_batch_size() {
+        let mut batch = ThumbnailBatch::default();
+
+        for _ in 1..THUMBNAILS_BATCH_SIZE {
+            assert!(!batch.add(0));
+        }
+        assert!(batch.add(0));
+
+        batch.reset();
+        assert!(!batch.add(0));
     }
 }
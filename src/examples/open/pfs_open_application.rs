@@ -8,8 +8,9 @@
 
 use adw::prelude::*;
 use adw::subclass::prelude::*;
-use gtk::{gdk, gio, glib};
+use gtk::{gio, glib};
 use std::cell::RefCell;
+use std::path::PathBuf;
 
 use pfs::file_props::FileProps;
 use pfs::file_selector::{FileSelector, FileSelectorBuilder, FileSelectorMode};
@@ -105,6 +106,15 @@ mod imp {
     impl ObjectImpl for PfsOpenApplication {
         fn constructed(&self) {
             self.parent_constructed();
+
+            self.obj().add_main_option(
+                "select",
+                glib::Char::from(b's'),
+                glib::OptionFlags::NONE,
+                glib::OptionArg::Filename,
+                &gettextrs::gettext("Show FILE selected in its folder"),
+                Some("FILE"),
+            );
         }
     }
 
@@ -122,6 +132,54 @@ mod imp {
             }
         }
 
+        fn command_line(&self, command_line: &gio::ApplicationCommandLine) -> glib::ExitCode {
+            let obj = self.obj();
+            let options = command_line.options_dict();
+
+            let select = options
+                .lookup::<PathBuf>("select")
+                .ok()
+                .flatten()
+                .map(|path| command_line.create_file_for_arg(path));
+            // The first argument is the program name
+            let args = command_line.arguments().into_iter().skip(1);
+            let dirs = args
+                .map(|arg| command_line.create_file_for_arg(arg))
+                .collect::<Vec<_>>();
+
+            for file in select.iter().chain(dirs.iter()) {
+                if !file.query_exists(None::<&gio::Cancellable>) {
+                    let msg = gettextrs::gettext("No such file or directory: {}").replacen(
+                        "{}",
+                        &file.parse_name(),
+                        1,
+                    );
+                    command_line.printerr_literal(&format!("{msg}\n"));
+                    return glib::ExitCode::FAILURE;
+                }
+            }
+
+            if let Some(file) = select {
+                obj.select_item(&file);
+            }
+
+            for dir in dirs {
+                let file_type =
+                    dir.query_file_type(gio::FileQueryInfoFlags::NONE, None::<&gio::Cancellable>);
+                if file_type == gio::FileType::Directory {
+                    obj.open_directory(&dir);
+                } else {
+                    obj.select_item(&dir);
+                }
+            }
+
+            if obj.windows().is_empty() {
+                obj.activate();
+            }
+
+            glib::ExitCode::SUCCESS
+        }
+
         fn dbus_register(
             &self,
             connection: &gio::DBusConnection,
@@ -187,7 +245,10 @@ impl PfsOpenApplication {
     pub fn new(application_id: &str) -> Self {
         glib::Object::builder()
             .property("application-id", application_id)
-            .property("flags", gio::ApplicationFlags::HANDLES_OPEN)
+            .property(
+                "flags",
+                gio::ApplicationFlags::HANDLES_OPEN | gio::ApplicationFlags::HANDLES_COMMAND_LINE,
+            )
             .build()
     }
 
@@ -203,29 +264,22 @@ impl PfsOpenApplication {
     }
 
     fn launch_for_uri(&self, uri: &str, parent: &FileSelector) {
-        let ctx = gdk::Display::default().map(|d| d.app_launch_context());
-
-        gio::AppInfo::launch_default_for_uri_async(
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            #[weak(rename_to = win)]
+            parent,
+            #[to_owned]
             uri,
-            ctx.as_ref(),
-            None::<&gio::Cancellable>,
-            glib::clone!(
-                #[weak(rename_to = this)]
-                self,
-                #[weak(rename_to = win)]
-                parent,
-                #[to_owned]
-                uri,
-                move |result| {
-                    if result.is_err() {
-                        let msg = &gettextrs::gettext("Failed to open {}").replace("{}", &uri);
-                        this.show_open_error(&win, msg);
-                    } else {
-                        glib::g_debug!(LOG_DOMAIN, "Launched {uri}");
-                    }
-                },
-            ),
-        );
+            async move {
+                if pfs::open::launch_uri(&uri).await.is_err() {
+                    let msg = &gettextrs::gettext("Failed to open {}").replace("{}", &uri);
+                    this.show_open_error(&win, msg);
+                } else {
+                    glib::g_debug!(LOG_DOMAIN, "Launched {uri}");
+                }
+            }
+        ));
     }
 
     fn open_directory(&self, dir: &gio::File) -> FileSelector {
@@ -0,0 +1,5 @@
+#[allow(dead_code)]
+pub static VERSION: &str = "0.1.0";
+pub static GETTEXT_PACKAGE: &str = "pfs";
+pub static LOG_DOMAIN: &str = "pfs";
+pub static LOCALEDIR: &str = "/usr/local/share/locale";
@@ -0,0 +1,521 @@
+/*
+ * Copyright 2025 Phosh.mobi e.V.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ *
+ * Author: Guido Günther <agx@sigxcpu.org>
+ */
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use pfs::file_selector::{FileSelector, FileSelectorBuilder, FileSelectorMode};
+
+use crate::config::LOG_DOMAIN;
+
+const PORTAL_NAME: &str = "org.freedesktop.impl.portal.desktop.pfs";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const FILE_CHOOSER_XML: &str = r#"
+<node>
+  <interface name='org.freedesktop.impl.portal.FileChooser'>
+    <method name='OpenFile'>
+      <arg type='o' name='handle' direction='in'/>
+      <arg type='s' name='app_id' direction='in'/>
+      <arg type='s' name='parent_window' direction='in'/>
+      <arg type='s' name='title' direction='in'/>
+      <arg type='a{sv}' name='options' direction='in'/>
+      <arg type='u' name='response' direction='out'/>
+      <arg type='a{sv}' name='results' direction='out'/>
+    </method>
+    <method name='SaveFile'>
+      <arg type='o' name='handle' direction='in'/>
+      <arg type='s' name='app_id' direction='in'/>
+      <arg type='s' name='parent_window' direction='in'/>
+      <arg type='s' name='title' direction='in'/>
+      <arg type='a{sv}' name='options' direction='in'/>
+      <arg type='u' name='response' direction='out'/>
+      <arg type='a{sv}' name='results' direction='out'/>
+    </method>
+    <method name='SaveFiles'>
+      <arg type='o' name='handle' direction='in'/>
+      <arg type='s' name='app_id' direction='in'/>
+      <arg type='s' name='parent_window' direction='in'/>
+      <arg type='s' name='title' direction='in'/>
+      <arg type='a{sv}' name='options' direction='in'/>
+      <arg type='u' name='response' direction='out'/>
+      <arg type='a{sv}' name='results' direction='out'/>
+    </method>
+  </interface>
+</node>
+"#;
+const REQUEST_XML: &str = r#"
+<node>
+  <interface name='org.freedesktop.impl.portal.Request'>
+    <method name='Close'/>
+  </interface>
+</node>
+"#;
+
+// Response codes as defined by the portal spec
+const RESPONSE_SUCCESS: u32 = 0;
+const RESPONSE_CANCELLED: u32 = 1;
+const RESPONSE_OTHER: u32 = 2;
+
+#[derive(Debug, glib::Variant)]
+struct FileChooserRequest {
+    handle: glib::variant::ObjectPath,
+    _app_id: String,
+    _parent_window: String,
+    title: String,
+    options: glib::VariantDict,
+}
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug)]
+enum FileChooser {
+    OpenFile(FileChooserRequest),
+    SaveFile(FileChooserRequest),
+    SaveFiles(FileChooserRequest),
+}
+
+#[derive(Debug)]
+enum Request {
+    Close,
+}
+
+// Paths are passed as nul terminated byte strings
+fn path_from_bytes(mut bytes: Vec<u8>) -> PathBuf {
+    if bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    PathBuf::from(OsStr::from_bytes(&bytes))
+}
+
+mod imp {
+    use super::*;
+
+    impl DBusMethodCall for FileChooser {
+        fn parse_call(
+            _obj_path: &str,
+            _interface: Option<&str>,
+            method: &str,
+            params: glib::Variant,
+        ) -> Result<Self, glib::Error> {
+            match method {
+                "OpenFile" => Ok(params.get::<FileChooserRequest>().map(Self::OpenFile)),
+                "SaveFile" => Ok(params.get::<FileChooserRequest>().map(Self::SaveFile)),
+                "SaveFiles" => Ok(params.get::<FileChooserRequest>().map(Self::SaveFiles)),
+                _ => Err(glib::Error::new(
+                    gio::DBusError::UnknownMethod,
+                    "No such method",
+                )),
+            }
+            .and_then(|p| {
+                p.ok_or_else(|| glib::Error::new(gio::DBusError::InvalidArgs, "Invalid parameters"))
+            })
+        }
+    }
+
+    impl DBusMethodCall for Request {
+        fn parse_call(
+            _obj_path: &str,
+            _interface: Option<&str>,
+            method: &str,
+            _params: glib::Variant,
+        ) -> Result<Self, glib::Error> {
+            match method {
+                "Close" => Ok(Self::Close),
+                _ => Err(glib::Error::new(
+                    gio::DBusError::UnknownMethod,
+                    "No such method",
+                )),
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct PfsPortalApplication {
+        registration_id: RefCell<Option<gio::RegistrationId>>,
+        owner_id: RefCell<Option<gio::OwnerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PfsPortalApplication {
+        const NAME: &'static str = "PfsPortalApplication";
+        type Type = super::PfsPortalApplication;
+        type ParentType = adw::Application;
+    }
+
+    impl ObjectImpl for PfsPortalApplication {}
+
+    impl ApplicationImpl for PfsPortalApplication {
+        fn dbus_register(
+            &self,
+            connection: &gio::DBusConnection,
+            object_path: &str,
+        ) -> Result<(), glib::Error> {
+            self.parent_dbus_register(connection, object_path)?;
+
+            if let Ok(id) = self.obj().register_object(connection) {
+                glib::g_debug!(LOG_DOMAIN, "Exported FileChooser DBus interface");
+                self.registration_id.replace(Some(id));
+            } else {
+                glib::g_warning!(LOG_DOMAIN, "Failed to export FileChooser DBus interface");
+            }
+
+            let id = gio::bus_own_name_on_connection(
+                connection,
+                PORTAL_NAME,
+                gio::BusNameOwnerFlags::REPLACE | gio::BusNameOwnerFlags::ALLOW_REPLACEMENT,
+                |_connection, name| {
+                    glib::g_debug!(LOG_DOMAIN, "Owned {name} DBus name");
+                },
+                glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_connection, name| {
+                        glib::g_warning!(LOG_DOMAIN, "Lost {name} DBus name");
+                        this.owner_id.replace(None);
+                    }
+                ),
+            );
+
+            self.owner_id.replace(Some(id));
+            Ok(())
+        }
+
+        fn dbus_unregister(&self, connection: &gio::DBusConnection, object_path: &str) {
+            self.parent_dbus_unregister(connection, object_path);
+            if let Some(id) = self.registration_id.take() {
+                if connection.unregister_object(id).is_ok() {
+                    glib::g_debug!(LOG_DOMAIN, "Unregistered object");
+                } else {
+                    glib::g_warning!(LOG_DOMAIN, "Could not unregister object");
+                }
+            }
+
+            if let Some(owner_id) = self.owner_id.replace(None) {
+                gio::bus_unown_name(owner_id);
+            }
+        }
+    }
+
+    impl GtkApplicationImpl for PfsPortalApplication {}
+    impl AdwApplicationImpl for PfsPortalApplication {}
+}
+
+glib::wrapper! {
+    pub struct PfsPortalApplication(ObjectSubclass<imp::PfsPortalApplication>)
+        @extends gio::Application, gtk::Application, adw::Application,
+        @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl PfsPortalApplication {
+    pub fn new(application_id: &str) -> Self {
+        glib::Object::builder()
+            .property("application-id", application_id)
+            .property("flags", gio::ApplicationFlags::IS_SERVICE)
+            .build()
+    }
+
+    // Converts the portal's filter list into a list model of file filters
+    fn filters_from_options(options: &glib::VariantDict) -> gio::ListStore {
+        let store = gio::ListStore::new::<gtk::FileFilter>();
+
+        if let Some(filters) =
+            options.lookup_value("filters", Some(glib::VariantTy::new("a(sa(us))").unwrap()))
+        {
+            for filter in filters.iter() {
+                store.append(&gtk::FileFilter::from_gvariant(&filter));
+            }
+        }
+
+        store
+    }
+
+    // The position of the current filter within `filters`, adding it if needed
+    fn current_filter_from_options(options: &glib::VariantDict, filters: &gio::ListStore) -> u32 {
+        let Some(current) = options.lookup_value(
+            "current_filter",
+            Some(glib::VariantTy::new("(sa(us))").unwrap()),
+        ) else {
+            return gtk::INVALID_LIST_POSITION;
+        };
+
+        let current = gtk::FileFilter::from_gvariant(&current);
+        let pos = filters
+            .iter::<gtk::FileFilter>()
+            .flatten()
+            .position(|filter| filter.name() == current.name());
+
+        match pos {
+            Some(pos) => pos as u32,
+            None => {
+                // The spec allows a current filter without any filters
+                filters.append(&current);
+                filters.n_items() - 1
+            }
+        }
+    }
+
+    fn build_file_selector(
+        &self,
+        mode: FileSelectorMode,
+        title: &str,
+        options: &glib::VariantDict,
+    ) -> FileSelector {
+        let filters = Self::filters_from_options(options);
+        let current_filter = Self::current_filter_from_options(options, &filters);
+
+        let accept_label = options
+            .lookup::<String>("accept_label")
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| match mode {
+                FileSelectorMode::OpenFile => gettextrs::gettext("_Open"),
+                FileSelectorMode::SaveFile | FileSelectorMode::SaveFiles => {
+                    gettextrs::gettext("_Save")
+                }
+            });
+
+        let mut builder = FileSelectorBuilder::new()
+            .accept_label(&accept_label)
+            .title(title)
+            .filters(filters.upcast())
+            .current_filter(current_filter);
+
+        if let Some(choices) = options.lookup_value(
+            "choices",
+            Some(glib::VariantTy::new("a(ssa(ss)s)").unwrap()),
+        ) {
+            builder = builder.choices(choices);
+        }
+
+        let mut folder = options
+            .lookup::<Vec<u8>>("current_folder")
+            .ok()
+            .flatten()
+            .map(|bytes| gio::File::for_path(path_from_bytes(bytes)));
+
+        if mode == FileSelectorMode::SaveFile {
+            // `current_file` takes precedence over `current_name`
+            if let Some(bytes) = options.lookup::<Vec<u8>>("current_file").ok().flatten() {
+                let file = gio::File::for_path(path_from_bytes(bytes));
+                folder = file.parent();
+                if let Some(name) = file.basename() {
+                    builder = builder.filename(&name.to_string_lossy());
+                }
+            } else if let Some(name) = options.lookup::<String>("current_name").ok().flatten() {
+                builder = builder.filename(&name);
+            }
+        }
+
+        if let Some(folder) = folder {
+            builder = builder.current_folder(folder);
+        }
+
+        if options.lookup::<bool>("multiple").ok().flatten() == Some(true) {
+            glib::g_debug!(LOG_DOMAIN, "Selecting multiple files not supported yet");
+        }
+
+        let file_selector = builder.build();
+        file_selector.set_mode(mode);
+
+        if mode == FileSelectorMode::OpenFile
+            && options.lookup::<bool>("directory").ok().flatten() == Some(true)
+        {
+            file_selector.set_directory(true);
+        }
+
+        file_selector
+    }
+
+    // Builds the portal's results from the user's selection
+    fn results(
+        file_selector: &FileSelector,
+        options: &glib::VariantDict,
+    ) -> Option<HashMap<String, glib::Variant>> {
+        let selected = file_selector.selected()?;
+
+        let uris = if file_selector.mode() == FileSelectorMode::SaveFiles {
+            // We get the folder, the portal wants a URI per file
+            let folder = gio::File::for_uri(selected.first()?);
+            let files = options
+                .lookup::<Vec<Vec<u8>>>("files")
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+
+            files
+                .into_iter()
+                .map(|bytes| folder.child(path_from_bytes(bytes)).uri().to_string())
+                .collect()
+        } else {
+            selected
+        };
+
+        let mut results = HashMap::new();
+        results.insert("uris".to_string(), uris.to_variant());
+
+        if let Some(choices) = file_selector.selected_choices() {
+            results.insert("choices".to_string(), choices);
+        }
+
+        if let Some(filter) = file_selector.active_filter() {
+            results.insert("current_filter".to_string(), filter.to_gvariant());
+        }
+
+        Some(results)
+    }
+
+    // Lets the portal frontend close the dialog e.g. when the app went away
+    fn register_request(
+        &self,
+        connection: &gio::DBusConnection,
+        handle: &str,
+        file_selector: &FileSelector,
+    ) -> Option<gio::RegistrationId> {
+        let request = gio::DBusNodeInfo::for_xml(REQUEST_XML)
+            .ok()
+            .and_then(|e| e.lookup_interface("org.freedesktop.impl.portal.Request"))
+            .expect("Request interface");
+
+        connection
+            .register_object(handle, &request)
+            .typed_method_call::<Request>()
+            .invoke_and_return(glib::clone!(
+                #[weak]
+                file_selector,
+                #[upgrade_or]
+                Ok(None),
+                move |_, _, call| {
+                    match call {
+                        Request::Close => {
+                            glib::g_debug!(LOG_DOMAIN, "Closing request");
+                            file_selector.close();
+                        }
+                    }
+                    Ok(None)
+                }
+            ))
+            .build()
+            .inspect_err(|err| glib::g_warning!(LOG_DOMAIN, "Failed to export request: {err}"))
+            .ok()
+    }
+
+    fn handle_request(
+        &self,
+        connection: gio::DBusConnection,
+        mode: FileSelectorMode,
+        request: FileChooserRequest,
+        invocation: gio::DBusMethodInvocation,
+    ) {
+        let FileChooserRequest {
+            handle,
+            title,
+            options,
+            ..
+        } = request;
+
+        glib::g_message!(
+            LOG_DOMAIN,
+            "File chooser request {mode:?} for {}",
+            handle.as_str()
+        );
+
+        let file_selector = self.build_file_selector(mode, &title, &options);
+        let request_id = self.register_request(&connection, handle.as_str(), &file_selector);
+        let request_id = RefCell::new(request_id);
+
+        file_selector.connect_closure(
+            "done",
+            false,
+            glib::closure_local!(
+                #[strong]
+                invocation,
+                #[strong]
+                connection,
+                move |selector: FileSelector, success: bool| {
+                    glib::g_debug!(LOG_DOMAIN, "File dialog done, result: {success}");
+
+                    if let Some(id) = request_id.take() {
+                        let _ = connection.unregister_object(id);
+                    }
+
+                    let (response, results) = if success {
+                        match Self::results(&selector, &options) {
+                            Some(results) => (RESPONSE_SUCCESS, results),
+                            None => (RESPONSE_OTHER, HashMap::new()),
+                        }
+                    } else {
+                        (RESPONSE_CANCELLED, HashMap::new())
+                    };
+
+                    invocation
+                        .clone()
+                        .return_value(Some(&(response, results).to_variant()));
+                }
+            ),
+        );
+
+        // TODO: Make the dialog transient for `parent_window`
+        let app = self.upcast_ref::<gtk::Application>();
+        app.add_window(file_selector.upcast_ref::<gtk::Window>());
+
+        file_selector.present();
+    }
+
+    fn register_object(
+        &self,
+        connection: &gio::DBusConnection,
+    ) -> Result<gio::RegistrationId, glib::Error> {
+        let file_chooser = gio::DBusNodeInfo::for_xml(FILE_CHOOSER_XML)
+            .ok()
+            .and_then(|e| e.lookup_interface("org.freedesktop.impl.portal.FileChooser"))
+            .expect("FileChooser interface");
+
+        connection
+            .register_object(PORTAL_OBJECT_PATH, &file_chooser)
+            .typed_method_call::<FileChooser>()
+            .invoke(glib::clone!(
+                #[weak_allow_none(rename_to = this)]
+                self,
+                move |connection, sender, call, invocation| {
+                    glib::g_message!(LOG_DOMAIN, "Method call from {sender:?}");
+
+                    let Some(this) = this else {
+                        invocation.return_error(gio::DBusError::Failed, "Application went away");
+                        return;
+                    };
+
+                    match call {
+                        FileChooser::OpenFile(request) => this.handle_request(
+                            connection,
+                            FileSelectorMode::OpenFile,
+                            request,
+                            invocation,
+                        ),
+                        FileChooser::SaveFile(request) => this.handle_request(
+                            connection,
+                            FileSelectorMode::SaveFile,
+                            request,
+                            invocation,
+                        ),
+                        FileChooser::SaveFiles(request) => this.handle_request(
+                            connection,
+                            FileSelectorMode::SaveFiles,
+                            request,
+                            invocation,
+                        ),
+                    }
+                }
+            ))
+            .build()
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * Copyright 2025 Phosh.mobi e.V.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ *
+ * Author: Guido Günther <agx@sigxcpu.org>
+ */
+
+//! A [`gtk::FileDialog`] like API on top of [`FileSelector`].
+//!
+//! This eases porting applications written against GTK's file chooser
+//! APIs: Create a [`FileDialog`], configure it via its properties and
+//! await one of [`FileDialog::open`], [`FileDialog::save`] or
+//! [`FileDialog::select_folder`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use gtk::prelude::*;
+//! use pfs::file_dialog::FileDialog;
+//!
+//! # async fn run() {
+//! let dialog = FileDialog::new();
+//! dialog.set_title("Open Image");
+//!
+//! match dialog.open(None::<&gtk::Window>).await {
+//!     Ok(file) => println!("Selected {}", file.uri()),
+//!     Err(err) => println!("No file selected: {err}"),
+//! }
+//! # }
+//! ```
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::Properties;
+use gtk::{gio, glib};
+use std::cell::{Cell, RefCell};
+
+use crate::{
+    config::LOG_DOMAIN,
+    file_selector::{FileSelector, FileSelectorBuilder, FileSelectorMode},
+};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, Properties)]
+    #[properties(wrapper_type = super::FileDialog)]
+    pub struct FileDialog {
+        // The title of the file selector window
+        #[property(get, set)]
+        pub title: RefCell<String>,
+
+        // The label of the accept button, a default is used when empty
+        #[property(get, set)]
+        pub accept_label: RefCell<String>,
+
+        // The filters to offer
+        #[property(get, set, nullable)]
+        pub filters: RefCell<Option<gio::ListModel>>,
+
+        // The filter selected initially, should be part of `filters`
+        #[property(get, set, nullable)]
+        pub default_filter: RefCell<Option<gtk::FileFilter>>,
+
+        // The folder to show initially
+        #[property(get, set, nullable)]
+        pub initial_folder: RefCell<Option<gio::File>>,
+
+        // The suggested file name when saving
+        #[property(get, set)]
+        pub initial_name: RefCell<String>,
+
+        // Whether the file selector is modal to its parent
+        #[property(get, set, construct, default = true)]
+        pub modal: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FileDialog {
+        const NAME: &'static str = "PfsFileDialog";
+        type Type = super::FileDialog;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for FileDialog {}
+}
+
+glib::wrapper! {
+    /// Asynchronously asks the user for files using a [`FileSelector`].
+    pub struct FileDialog(ObjectSubclass<imp::FileDialog>);
+}
+
+impl Default for FileDialog {
+    fn default() -> Self {
+        glib::Object::new::<Self>()
+    }
+}
+
+impl FileDialog {
+    /// Creates a new `FileDialog`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lets the user select an existing file.
+    ///
+    /// Returns [`gtk::DialogError::Dismissed`] when the user cancelled.
+    pub async fn open(
+        &self,
+        parent: Option<&impl IsA<gtk::Window>>,
+    ) -> Result<gio::File, glib::Error> {
+        self.choose(gtk::FileChooserAction::Open, parent).await
+    }
+
+    /// Lets the user pick a file name to save to, starting with
+    /// `initial-name`.
+    ///
+    /// Returns [`gtk::DialogError::Dismissed`] when the user cancelled.
+    pub async fn save(
+        &self,
+        parent: Option<&impl IsA<gtk::Window>>,
+    ) -> Result<gio::File, glib::Error> {
+        self.choose(gtk::FileChooserAction::Save, parent).await
+    }
+
+    /// Lets the user select a folder.
+    ///
+    /// Returns [`gtk::DialogError::Dismissed`] when the user cancelled.
+    pub async fn select_folder(
+        &self,
+        parent: Option<&impl IsA<gtk::Window>>,
+    ) -> Result<gio::File, glib::Error> {
+        self.choose(gtk::FileChooserAction::SelectFolder, parent)
+            .await
+    }
+
+    /// Shows a [`FileSelector`] for the given `action`.
+    ///
+    /// This is useful when porting code that passes around a
+    /// [`gtk::FileChooserAction`]. Otherwise prefer [`Self::open`],
+    /// [`Self::save`] or [`Self::select_folder`].
+    pub async fn choose(
+        &self,
+        action: gtk::FileChooserAction,
+        parent: Option<&impl IsA<gtk::Window>>,
+    ) -> Result<gio::File, glib::Error> {
+        let file_selector = self.build_file_selector(action);
+
+        file_selector.set_transient_for(parent);
+        file_selector.set_modal(self.modal());
+
+        let uri = gio::GioFuture::new(&file_selector, |file_selector, _, result| {
+            let result = RefCell::new(Some(result));
+
+            file_selector.connect_closure(
+                "done",
+                false,
+                glib::closure_local!(move |selector: FileSelector, success: bool| {
+                    let Some(result) = result.take() else {
+                        return;
+                    };
+
+                    let uri = if success {
+                        selector.selected().and_then(|uris| uris.into_iter().next())
+                    } else {
+                        None
+                    };
+                    result.resolve(uri);
+                }),
+            );
+
+            file_selector.present();
+        })
+        .await;
+
+        match uri {
+            Some(uri) => Ok(gio::File::for_uri(&uri)),
+            None => Err(glib::Error::new(
+                gtk::DialogError::Dismissed,
+                "Dismissed by user",
+            )),
+        }
+    }
+
+    fn build_file_selector(&self, action: gtk::FileChooserAction) -> FileSelector {
+        let (mode, directory) = match action {
+            gtk::FileChooserAction::Save => (FileSelectorMode::SaveFile, false),
+            gtk::FileChooserAction::SelectFolder => (FileSelectorMode::OpenFile, true),
+            gtk::FileChooserAction::Open => (FileSelectorMode::OpenFile, false),
+            _ => {
+                glib::g_warning!(LOG_DOMAIN, "Unknown action {action:?}, opening a file");
+                (FileSelectorMode::OpenFile, false)
+            }
+        };
+
+        let accept_label = match self.accept_label() {
+            label if !label.is_empty() => label,
+            _ if mode == FileSelectorMode::SaveFile => gettextrs::gettext("_Save"),
+            _ if directory => gettextrs::gettext("_Select"),
+            _ => gettextrs::gettext("_Open"),
+        };
+
+        let mut builder = FileSelectorBuilder::new()
+            .title(&self.title())
+            .accept_label(&accept_label);
+
+        if let Some(filters) = self.filters() {
+            let default_filter = self.default_filter();
+            let pos = filters
+                .iter::<gtk::FileFilter>()
+                .flatten()
+                .position(|filter| Some(&filter) == default_filter.as_ref());
+
+            if let Some(pos) = pos {
+                builder = builder.current_filter(pos as u32);
+            }
+            builder = builder.filters(filters);
+        }
+
+        if let Some(folder) = self.initial_folder() {
+            builder = builder.current_folder(folder);
+        }
+
+        if mode == FileSelectorMode::SaveFile {
+            builder = builder.filename(&self.initial_name());
+        }
+
+        let file_selector = builder.build();
+        file_selector.set_mode(mode);
+        file_selector.set_directory(directory);
+
+        file_selector
+    }
+}
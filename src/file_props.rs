@@ -20,7 +20,16 @@ use gtk::{gdk, gio, glib, CompositeTemplate};
 use std::cell::{Cell, RefCell};
 use std::sync::OnceLock;
 
-use crate::{config::LOG_DOMAIN, file_selector::FileSelector, file_selector::FileSelectorMode};
+use crate::{
+    config::LOG_DOMAIN, dir_view::DirView, file_selector::FileSelector,
+    file_selector::FileSelectorMode, util,
+};
+
+// Don't descend further than this when summing up directory sizes
+const DIR_SIZE_MAX_DEPTH: usize = 64;
+const DIR_SIZE_BATCH_SIZE: i32 = 100;
+// Read files in chunks of this size when calculating checksums
+const CHECKSUM_CHUNK_SIZE: usize = 64 * 1024;
 
 /// The type of filesystem entry being displayed in [`FileProps`].
 #[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
@@ -31,7 +40,8 @@ pub enum FilePropsType {
     File,
     /// A directory.
     Directory,
-    //TODO: MountPoint,
+    /// The root of a mounted file system.
+    MountPoint,
 }
 
 /// Implementation details for [`FileProps`].
@@ -51,6 +61,9 @@ pub mod imp {
         #[template_child]
         pub size_label: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub name_row: TemplateChild<adw::EntryRow>,
+
         #[template_child]
         pub access_row: TemplateChild<adw::ActionRow>,
 
@@ -63,9 +76,72 @@ pub mod imp {
         #[template_child]
         pub timestamp_group: TemplateChild<adw::PreferencesGroup>,
 
+        #[template_child]
+        pub filesystem_group: TemplateChild<adw::PreferencesGroup>,
+
+        #[template_child]
+        pub capacity_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub fs_type_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub device_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub open_with_group: TemplateChild<adw::PreferencesGroup>,
+
+        #[template_child]
+        pub open_with_expander: TemplateChild<adw::ExpanderRow>,
+
+        #[template_child]
+        pub default_app_row: TemplateChild<adw::SwitchRow>,
+
+        #[template_child]
+        pub link_group: TemplateChild<adw::PreferencesGroup>,
+
+        #[template_child]
+        pub link_target_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub broken_link_icon: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub open_link_target_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub permissions_group: TemplateChild<adw::PreferencesGroup>,
+
+        #[template_child]
+        pub checksum_expander: TemplateChild<adw::ExpanderRow>,
+
+        #[template_child]
+        pub checksum_progress_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub checksum_progress: TemplateChild<gtk::ProgressBar>,
+
+        #[template_child]
+        pub md5_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub sha256_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub permissions_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub owner_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub executable_row: TemplateChild<adw::SwitchRow>,
+
         #[template_child]
         pub toast_overlay: TemplateChild<adw::ToastOverlay>,
 
+        #[template_child]
+        pub name_label: TemplateChild<gtk::Label>,
+
         // The file we show the info for
         #[property(get, set, construct)]
         pub file: RefCell<Option<gio::File>>,
@@ -76,6 +152,26 @@ pub mod imp {
         #[property(get, explicit_notify, builder(FilePropsType::default()))]
         pub file_type: RefCell<FilePropsType>,
 
+        // The file's unix mode, if known
+        pub(super) unix_mode: Cell<Option<u32>>,
+        pub(super) updating_permissions: Cell<bool>,
+
+        // Cancels long running operations like summing up directory sizes
+        pub(super) cancellable: gio::Cancellable,
+
+        pub(super) file_size: Cell<u64>,
+        pub(super) content_type: RefCell<Option<String>>,
+        pub(super) open_with_rows: RefCell<Vec<adw::ActionRow>>,
+        pub(super) checksums_started: Cell<bool>,
+
+        // The resolved target if the file is a symlink
+        pub(super) link_target: RefCell<Option<gio::File>>,
+        pub(super) link_target_is_dir: Cell<bool>,
+
+        // The view the file is shown in, renames go through it so they
+        // can be undone
+        pub(super) dir_view: glib::WeakRef<DirView>,
+
         done: Cell<bool>,
     }
 
@@ -89,6 +185,18 @@ pub mod imp {
             klass.bind_template();
             klass.bind_template_instance_callbacks();
 
+            klass.install_action("props.copy-path", None, move |props, _, _| {
+                if let Some(path) = props.file().and_then(|file| file.path()) {
+                    props.copy_to_clipboard(&path.to_string_lossy());
+                }
+            });
+
+            klass.install_action("props.copy-uri", None, move |props, _, _| {
+                if let Some(file) = props.file() {
+                    props.copy_to_clipboard(&file.uri());
+                }
+            });
+
             klass.add_binding_action(
                 gdk::Key::Escape,
                 gdk::ModifierType::NO_MODIFIER_MASK,
@@ -109,6 +217,8 @@ pub mod imp {
             let obj = self.obj();
 
             obj.setup_fileinfo();
+            obj.update_copy_actions();
+            obj.connect_file_notify(|obj| obj.update_copy_actions());
         }
 
         fn signals() -> &'static [Signal] {
@@ -184,54 +294,44 @@ impl FileProps {
         let mut have_thumbnail = false;
         let mut have_timestamp = false;
 
+        imp.name_label.set_label(&info.display_name());
+        imp.name_row.set_text(&info.display_name());
+        imp.name_row
+            .set_sensitive(info.attribute_boolean("access::can-rename"));
+
         let size = info.size();
-        imp.size_label.set_label(&glib::format_size(size as u64));
+        imp.file_size.set(size.max(0) as u64);
+        imp.size_label.set_label(&util::format_size(size as u64));
         imp.size_label.set_visible(true);
 
-        if let Some(created) = info.creation_date_time() {
-            if let Ok(fmt) = created.format_iso8601() {
-                imp.created_row.set_subtitle(&fmt);
-                imp.created_row.set_visible(true);
-                have_timestamp = true;
-            }
-        }
-
-        if let Some(modified) = info.modification_date_time() {
-            if let Ok(fmt) = modified.format_iso8601() {
-                imp.modified_row.set_subtitle(&fmt);
-                imp.modified_row.set_visible(true);
-                have_timestamp = true;
-            }
-        }
-
-        if let Some(access) = info.access_date_time() {
-            if let Ok(fmt) = access.format_iso8601() {
-                imp.access_row.set_subtitle(&fmt);
-                imp.access_row.set_visible(true);
-                have_timestamp = true;
-            }
-        }
+        have_timestamp |= Self::set_timestamp(&imp.created_row, info.creation_date_time());
+        have_timestamp |= Self::set_timestamp(&imp.modified_row, info.modification_date_time());
+        have_timestamp |= Self::set_timestamp(&imp.access_row, info.access_date_time());
 
         if have_timestamp {
             imp.timestamp_group.set_visible(true);
         }
 
+        self.update_permissions(info);
+        self.update_link_target(info);
+
         if let Some(content_type) = info.content_type() {
             if content_type == "inode/directory" {
                 imp.file_type.replace(FilePropsType::Directory);
                 self.notify_file_type();
                 imp.type_label.set_label(&gettextrs::gettext("Directory"));
+                self.update_directory_info(info);
             } else {
                 imp.type_label.set_label(&content_type);
+                *imp.content_type.borrow_mut() = Some(content_type.to_string());
+                self.update_open_with();
             }
         }
 
-        if let Some(path) = info.attribute_byte_string("thumbnail::path") {
-            if info.boolean("thumbnail::is-valid") {
-                imp.icon.set_from_file(Some(path));
-                have_thumbnail = true;
-                imp.icon.set_pixel_size(256);
-            }
+        if let Some(path) = util::cached_thumbnail_path(info) {
+            imp.icon.set_from_file(Some(path));
+            have_thumbnail = true;
+            imp.icon.set_pixel_size(256);
         }
 
         if !have_thumbnail {
@@ -251,17 +351,689 @@ impl FileProps {
         }
     }
 
+    // Shows a friendly timestamp, the exact one is in the tooltip
+    fn set_timestamp(row: &adw::ActionRow, date_time: Option<glib::DateTime>) -> bool {
+        let Some(date_time) = date_time else {
+            return false;
+        };
+
+        let Ok(iso) = date_time.format_iso8601() else {
+            return false;
+        };
+
+        let fmt = util::format_date_time(&date_time).unwrap_or_else(|| iso.to_string());
+        row.set_subtitle(&fmt);
+        row.set_tooltip_text(Some(&iso));
+        row.set_visible(true);
+        true
+    }
+
+    // The mount if `file` is the root of one
+    async fn mount_for_root(file: &gio::File) -> Option<gio::Mount> {
+        let mount = file
+            .find_enclosing_mount_future(glib::Priority::DEFAULT)
+            .await
+            .ok()?;
+        mount.root().equal(file).then_some(mount)
+    }
+
+    // Looking up the mount can take a while on network file systems so
+    // the mount point details and the directory size follow once known
+    fn update_directory_info(&self, info: &gio::FileInfo) {
+        let Some(file) = self.file() else {
+            return;
+        };
+        let is_mountpoint = info.attribute_boolean("unix::is-mountpoint");
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let mount = Self::mount_for_root(&file).await;
+
+                // Renamed in the meantime
+                if !this.file().is_some_and(|current| current.equal(&file)) {
+                    return;
+                }
+
+                if mount.is_some() || is_mountpoint {
+                    this.update_mount_point(&file, mount);
+                } else {
+                    this.update_directory_size();
+                }
+                this.update_filesystem_info();
+            }
+        ));
+    }
+
+    fn update_mount_point(&self, file: &gio::File, mount: Option<gio::Mount>) {
+        let imp = self.imp();
+        imp.file_type.replace(FilePropsType::MountPoint);
+        self.notify_file_type();
+        imp.type_label.set_label(&gettextrs::gettext("Mount Point"));
+
+        // Prefer the device the mount table knows about, fall back to the volume's
+        let device = file
+            .path()
+            .and_then(|path| gio::UnixMountEntry::for_mount_path(path).0)
+            .map(|entry| entry.device_path().to_string_lossy().to_string())
+            .or_else(|| {
+                mount
+                    .and_then(|mount| mount.volume())
+                    .and_then(|volume| {
+                        volume.identifier(gio::VOLUME_IDENTIFIER_KIND_UNIX_DEVICE.as_str())
+                    })
+                    .map(|device| device.to_string())
+            });
+        if let Some(device) = device {
+            imp.device_row.set_subtitle(&device);
+            imp.device_row.set_visible(true);
+            imp.filesystem_group.set_visible(true);
+        }
+    }
+
+    // Free space of the file system containing the folder, plus more
+    // details for mount points
+    fn update_filesystem_info(&self) {
+        let Some(file) = self.file() else {
+            return;
+        };
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let info = match file
+                    .query_filesystem_info_future(
+                        "filesystem::type,filesystem::size,filesystem::free,filesystem::used",
+                        glib::Priority::DEFAULT,
+                    )
+                    .await
+                {
+                    Ok(info) => info,
+                    Err(err) => {
+                        glib::g_debug!(LOG_DOMAIN, "Failed to get file system info: {err}");
+                        return;
+                    }
+                };
+
+                let imp = this.imp();
+                if this.file_type() == FilePropsType::MountPoint {
+                    if let Some(fs_type) = info.attribute_string("filesystem::type") {
+                        imp.fs_type_row.set_subtitle(&fs_type);
+                        imp.fs_type_row.set_visible(true);
+                        imp.filesystem_group.set_visible(true);
+                    }
+                }
+
+                if !info.has_attribute("filesystem::size") {
+                    return;
+                }
+
+                let size = info.attribute_uint64("filesystem::size");
+                let free = if info.has_attribute("filesystem::free") {
+                    info.attribute_uint64("filesystem::free")
+                } else if info.has_attribute("filesystem::used") {
+                    size.saturating_sub(info.attribute_uint64("filesystem::used"))
+                } else {
+                    return;
+                };
+
+                let capacity = gettextrs::gettext("{free} free of {size}")
+                    .replacen("{free}", &util::format_size(free), 1)
+                    .replacen("{size}", &util::format_size(size), 1);
+                imp.capacity_row.set_subtitle(&capacity);
+                imp.capacity_row.set_visible(true);
+                imp.filesystem_group.set_visible(true);
+            }
+        ));
+    }
+
+    // Sums up the sizes of all files below the directory, updating the
+    // size label as we go
+    fn update_directory_size(&self) {
+        let Some(file) = self.file() else {
+            return;
+        };
+
+        let cancellable = self.imp().cancellable.clone();
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let size_label = this.imp().size_label.clone();
+                let mut total: u64 = 0;
+                let mut incomplete = false;
+                let mut dirs = vec![(file, 0)];
+
+                size_label.set_label(&gettextrs::gettext("Calculating…"));
+                while let Some((dir, depth)) = dirs.pop() {
+                    if cancellable.is_cancelled() {
+                        return;
+                    }
+
+                    let enumerator = match dir
+                        .enumerate_children_future(
+                            "standard::name,standard::size,standard::type",
+                            gio::FileQueryInfoFlags::NOFOLLOW_SYMLINKS,
+                            glib::Priority::LOW,
+                        )
+                        .await
+                    {
+                        Ok(enumerator) => enumerator,
+                        Err(err) => {
+                            glib::g_debug!(LOG_DOMAIN, "Can't enumerate {}: {err}", dir.uri());
+                            incomplete = true;
+                            continue;
+                        }
+                    };
+
+                    loop {
+                        if cancellable.is_cancelled() {
+                            return;
+                        }
+
+                        let infos = match enumerator
+                            .next_files_future(DIR_SIZE_BATCH_SIZE, glib::Priority::LOW)
+                            .await
+                        {
+                            Ok(infos) => infos,
+                            Err(err) => {
+                                glib::g_debug!(LOG_DOMAIN, "Can't list {}: {err}", dir.uri());
+                                incomplete = true;
+                                break;
+                            }
+                        };
+
+                        if infos.is_empty() {
+                            break;
+                        }
+
+                        for info in infos {
+                            total += info.size().max(0) as u64;
+
+                            if info.file_type() != gio::FileType::Directory {
+                                continue;
+                            }
+
+                            if depth < DIR_SIZE_MAX_DEPTH {
+                                dirs.push((dir.child(info.name()), depth + 1));
+                            } else {
+                                incomplete = true;
+                            }
+                        }
+
+                        let msg = gettextrs::gettext("Calculating… {}").replacen(
+                            "{}",
+                            &util::format_size(total),
+                            1,
+                        );
+                        size_label.set_label(&msg);
+                    }
+                }
+
+                let size = util::format_size(total);
+                let msg = if incomplete {
+                    gettextrs::gettext("At least {}").replacen("{}", &size, 1)
+                } else {
+                    size.to_string()
+                };
+                size_label.set_label(&msg);
+            }
+        ));
+    }
+
+    fn update_open_with(&self) {
+        let imp = self.imp();
+        let Some(content_type) = imp.content_type.borrow().clone() else {
+            return;
+        };
+
+        for row in imp.open_with_rows.borrow_mut().drain(..) {
+            imp.open_with_expander.remove(&row);
+        }
+
+        let default = gio::AppInfo::default_for_type(&content_type, false);
+        for app in gio::AppInfo::all_for_type(&content_type) {
+            let row = adw::ActionRow::builder()
+                .title(app.display_name())
+                .activatable(true)
+                .use_markup(false)
+                .build();
+            if let Some(icon) = app.icon() {
+                row.add_prefix(&gtk::Image::from_gicon(&icon));
+            }
+            if default.as_ref().is_some_and(|default| default.equal(&app)) {
+                row.set_subtitle(&gettextrs::gettext("Default"));
+            }
+
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |_| this.launch_app(&app)
+            ));
+            imp.open_with_expander.add_row(&row);
+            imp.open_with_rows.borrow_mut().push(row);
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(gettextrs::gettext("Other Application…"))
+            .activatable(true)
+            .build();
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_| this.choose_app()
+        ));
+        imp.open_with_expander.add_row(&row);
+        imp.open_with_rows.borrow_mut().push(row);
+
+        imp.open_with_group.set_visible(true);
+    }
+
+    #[allow(deprecated)]
+    fn choose_app(&self) {
+        let Some(file) = self.file() else {
+            return;
+        };
+
+        let dialog = gtk::AppChooserDialog::new(
+            Some(self),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &file,
+        );
+        dialog.connect_response(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |dialog, response| {
+                if response == gtk::ResponseType::Ok {
+                    if let Some(app) = dialog.app_info() {
+                        this.launch_app(&app);
+                    }
+                }
+                dialog.destroy();
+            }
+        ));
+        dialog.present();
+    }
+
+    fn launch_app(&self, app: &gio::AppInfo) {
+        let imp = self.imp();
+        let Some(file) = self.file() else {
+            return;
+        };
+
+        if imp.default_app_row.is_active() {
+            if let Some(content_type) = imp.content_type.borrow().as_ref() {
+                if let Err(err) = app.set_as_default_for_type(content_type) {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to set default app: {err}");
+                }
+            }
+            self.update_open_with();
+        }
+
+        let name = app.display_name();
+        glib::g_debug!(LOG_DOMAIN, "Opening {} with {name}", file.uri());
+        let ctx = self.display().app_launch_context();
+        if let Err(err) = app.launch(&[file], Some(&ctx)) {
+            glib::g_warning!(LOG_DOMAIN, "Failed to launch {name}: {err}");
+            let msg = gettextrs::gettext("Failed to launch {}").replacen("{}", &name, 1);
+            imp.toast_overlay.add_toast(adw::Toast::new(&msg));
+        }
+    }
+
+    // Only local files have a path
+    fn update_copy_actions(&self) {
+        let has_path = self.file().is_some_and(|file| file.path().is_some());
+        self.action_set_enabled("props.copy-path", has_path);
+    }
+
+    /// Routes renames through `dir_view` so they can be undone there.
+    pub(crate) fn set_dir_view(&self, dir_view: &DirView) {
+        self.imp().dir_view.set(Some(dir_view));
+    }
+
+    #[template_callback]
+    fn on_name_applied(&self) {
+        let Some(file) = self.file() else {
+            return;
+        };
+
+        let new_name = self.imp().name_row.text().to_string();
+        if new_name.is_empty() {
+            return;
+        }
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let imp = this.imp();
+
+                let result = match imp.dir_view.upgrade() {
+                    Some(dir_view) => {
+                        let old_name = imp.name_label.label();
+                        dir_view.rename_file(&file, &old_name, &new_name).await
+                    }
+                    None => {
+                        file.set_display_name_future(&new_name, glib::Priority::DEFAULT)
+                            .await
+                    }
+                };
+
+                match result {
+                    Ok(new_file) => {
+                        glib::g_debug!(LOG_DOMAIN, "Renamed to {}", new_file.uri());
+                        // The content type and thus the apps to open it with
+                        // can change with the name
+                        this.set_file(new_file);
+                        this.setup_fileinfo();
+                    }
+                    Err(err) => {
+                        let msg = if err.matches(gio::IOErrorEnum::Exists) {
+                            gettextrs::gettext("A file named “{}” already exists")
+                        } else if err.matches(gio::IOErrorEnum::InvalidFilename) {
+                            gettextrs::gettext("“{}” is not a valid file name")
+                        } else {
+                            gettextrs::gettext("Failed to rename to “{}”")
+                        }
+                        .replacen("{}", &new_name, 1);
+
+                        glib::g_warning!(LOG_DOMAIN, "Failed to rename {}: {err}", file.uri());
+                        imp.toast_overlay.add_toast(adw::Toast::new(&msg));
+
+                        // Show the current name again
+                        imp.name_row.set_text(&imp.name_label.label());
+                    }
+                }
+            }
+        ));
+    }
+
+    async fn calculate_checksums(
+        &self,
+        file: &gio::File,
+    ) -> Result<Option<(String, String)>, glib::Error> {
+        let imp = self.imp();
+        let stream = file.read_future(glib::Priority::LOW).await?;
+        let mut md5 = glib::Checksum::new(glib::ChecksumType::Md5).unwrap();
+        let mut sha256 = glib::Checksum::new(glib::ChecksumType::Sha256).unwrap();
+        let total = imp.file_size.get();
+        let mut read: u64 = 0;
+
+        loop {
+            if imp.cancellable.is_cancelled() {
+                return Ok(None);
+            }
+
+            let bytes = stream
+                .read_bytes_future(CHECKSUM_CHUNK_SIZE, glib::Priority::LOW)
+                .await?;
+            if bytes.is_empty() {
+                break;
+            }
+
+            md5.update(&bytes);
+            sha256.update(&bytes);
+
+            read += bytes.len() as u64;
+            if total > 0 {
+                imp.checksum_progress
+                    .set_fraction((read as f64 / total as f64).min(1.0));
+            }
+        }
+
+        Ok(md5.string().zip(sha256.string()))
+    }
+
+    #[template_callback]
+    fn on_checksum_expanded(&self) {
+        let imp = self.imp();
+
+        if !imp.checksum_expander.is_expanded() || imp.checksums_started.get() {
+            return;
+        }
+
+        let Some(file) = self.file() else {
+            return;
+        };
+
+        imp.checksums_started.set(true);
+        imp.checksum_expander.set_subtitle("");
+        imp.checksum_progress.set_fraction(0.0);
+        imp.checksum_progress_row.set_visible(true);
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let res = this.calculate_checksums(&file).await;
+                let imp = this.imp();
+
+                imp.checksum_progress_row.set_visible(false);
+                match res {
+                    Ok(Some((md5, sha256))) => {
+                        imp.md5_row.set_subtitle(&md5);
+                        imp.md5_row.set_visible(true);
+                        imp.sha256_row.set_subtitle(&sha256);
+                        imp.sha256_row.set_visible(true);
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        glib::g_warning!(LOG_DOMAIN, "Failed to calculate checksums: {err}");
+                        let msg = gettextrs::gettext("Failed to calculate checksums");
+                        imp.toast_overlay.add_toast(adw::Toast::new(&msg));
+                        // Allow to try again
+                        imp.checksums_started.set(false);
+                        imp.checksum_expander.set_expanded(false);
+                    }
+                }
+            }
+        ));
+    }
+
+    fn copy_to_clipboard(&self, text: &str) {
+        self.clipboard().set_text(text);
+
+        let toast = adw::Toast::builder()
+            .title(gettextrs::gettext("Copied to clipboard"))
+            .timeout(2)
+            .build();
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    #[template_callback]
+    fn on_copy_md5_clicked(&self) {
+        self.copy_to_clipboard(&self.imp().md5_row.subtitle().unwrap_or_default());
+    }
+
+    #[template_callback]
+    fn on_copy_sha256_clicked(&self) {
+        self.copy_to_clipboard(&self.imp().sha256_row.subtitle().unwrap_or_default());
+    }
+
+    fn update_link_target(&self, info: &gio::FileInfo) {
+        let imp = self.imp();
+
+        let target = info.symlink_target().filter(|_| info.is_symlink());
+        let (Some(file), Some(target)) = (self.file(), target) else {
+            *imp.link_target.borrow_mut() = None;
+            imp.link_group.set_visible(false);
+            return;
+        };
+
+        imp.link_target_row.set_subtitle(&target.to_string_lossy());
+        imp.link_group.set_visible(true);
+
+        // Relative targets are relative to the link's folder
+        let target = match file.parent() {
+            Some(parent) => parent.resolve_relative_path(&target),
+            None => gio::File::for_path(&target),
+        };
+        *imp.link_target.borrow_mut() = Some(target.clone());
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let imp = this.imp();
+                let res = target
+                    .query_info_future(
+                        "standard::type",
+                        gio::FileQueryInfoFlags::NONE,
+                        glib::Priority::DEFAULT,
+                    )
+                    .await;
+
+                let broken = match res {
+                    Ok(info) => {
+                        imp.link_target_is_dir
+                            .set(info.file_type() == gio::FileType::Directory);
+                        false
+                    }
+                    Err(err) => {
+                        glib::g_debug!(LOG_DOMAIN, "Link target not found: {err}");
+                        true
+                    }
+                };
+
+                imp.broken_link_icon.set_visible(broken);
+                imp.open_link_target_button.set_visible(!broken);
+                imp.link_target_row.set_activatable(!broken);
+                if broken {
+                    imp.link_target_row.add_css_class("error");
+                } else {
+                    imp.link_target_row.remove_css_class("error");
+                }
+            }
+        ));
+    }
+
+    #[template_callback]
+    fn on_open_link_target_clicked(&self) {
+        let imp = self.imp();
+        let Some(target) = imp.link_target.borrow().clone() else {
+            return;
+        };
+
+        // Show a folder's contents, a file within its folder
+        let folder = if imp.link_target_is_dir.get() {
+            Some(target)
+        } else {
+            target.parent()
+        };
+
+        let file_selector = glib::Object::builder::<FileSelector>()
+            .property("accept_label", gettextrs::gettext("Done"))
+            .property("title", gettextrs::gettext("Browse Directory"))
+            .property("current-folder", folder)
+            .build();
+
+        file_selector.set_mode(FileSelectorMode::OpenFile);
+        file_selector.present();
+    }
+
+    fn update_permissions(&self, info: &gio::FileInfo) {
+        let imp = self.imp();
+
+        // Not available on e.g. some remote file systems
+        if !info.has_attribute("unix::mode") {
+            imp.unix_mode.set(None);
+            imp.permissions_group.set_visible(false);
+            return;
+        }
+
+        let mode = info.attribute_uint32("unix::mode");
+        imp.unix_mode.set(Some(mode));
+        imp.permissions_row
+            .set_subtitle(&util::format_unix_mode(mode));
+
+        let user = info.attribute_string("owner::user");
+        let group = info.attribute_string("owner::group");
+        let owner = match (user, group) {
+            (Some(user), Some(group)) => Some(format!("{user}:{group}")),
+            (Some(user), None) => Some(user.to_string()),
+            (None, Some(group)) => Some(format!(":{group}")),
+            (None, None) => None,
+        };
+        imp.owner_row.set_visible(owner.is_some());
+        imp.owner_row.set_subtitle(&owner.unwrap_or_default());
+
+        imp.updating_permissions.set(true);
+        imp.executable_row.set_active(mode & 0o111 != 0);
+        imp.updating_permissions.set(false);
+        imp.executable_row
+            .set_visible(info.file_type() == gio::FileType::Regular);
+
+        imp.permissions_group.set_visible(true);
+    }
+
+    #[template_callback]
+    fn on_executable_toggled(&self) {
+        let imp = self.imp();
+
+        if imp.updating_permissions.get() {
+            return;
+        }
+
+        let (Some(file), Some(mode)) = (self.file(), imp.unix_mode.get()) else {
+            return;
+        };
+
+        let executable = imp.executable_row.is_active();
+        let new_mode = if executable {
+            // Like `chmod +x`: executable for everyone that can read it
+            mode | ((mode & 0o444) >> 2)
+        } else {
+            mode & !0o111
+        };
+
+        if new_mode == mode {
+            return;
+        }
+
+        match file.set_attribute_uint32(
+            "unix::mode",
+            new_mode,
+            gio::FileQueryInfoFlags::NOFOLLOW_SYMLINKS,
+            None::<&gio::Cancellable>,
+        ) {
+            Ok(()) => {
+                imp.unix_mode.set(Some(new_mode));
+                imp.permissions_row
+                    .set_subtitle(&util::format_unix_mode(new_mode));
+            }
+            Err(err) => {
+                glib::g_warning!(LOG_DOMAIN, "Failed to change permissions: {err}");
+                let msg = gettextrs::gettext("Failed to change permissions");
+                imp.toast_overlay.add_toast(adw::Toast::new(&msg));
+
+                imp.updating_permissions.set(true);
+                imp.executable_row.set_active(!executable);
+                imp.updating_permissions.set(false);
+            }
+        }
+    }
+
     fn clear_info(&self) {
         let imp = self.imp();
         let unknown = gettextrs::gettext("Unknown");
 
         imp.size_label.set_label(&unknown);
+        // Until we know the display name
+        imp.name_label.set_label(&self.file_to_string(self.file()));
 
         imp.size_label.set_visible(false);
         imp.timestamp_group.set_visible(false);
         imp.created_row.set_visible(false);
         imp.modified_row.set_visible(false);
         imp.access_row.set_visible(false);
+        imp.permissions_group.set_visible(false);
+        imp.link_group.set_visible(false);
+        imp.filesystem_group.set_visible(false);
+        imp.capacity_row.set_visible(false);
+        imp.fs_type_row.set_visible(false);
+        imp.device_row.set_visible(false);
         imp.type_label.set_label(&unknown);
         imp.icon.set_icon_name(Some("image-missing-symbolic"));
         imp.icon.set_pixel_size(128);
@@ -283,14 +1055,22 @@ impl FileProps {
                 match file
                     .query_info_future(
                         &[
+                            "access::can-rename",
                             "standard::content-type",
                             "standard::display-name",
                             "standard::icon",
+                            "standard::is-symlink",
                             "standard::size",
+                            "standard::symlink-target",
+                            "standard::type",
+                            "owner::group",
+                            "owner::user",
                             "thumbnail::*",
                             "time::access",
                             "time::created",
                             "time::modified",
+                            "unix::is-mountpoint",
+                            "unix::mode",
                         ]
                         .join(","),
                         gio::FileQueryInfoFlags::NOFOLLOW_SYMLINKS,
@@ -318,6 +1098,7 @@ impl FileProps {
 
     #[template_callback]
     fn on_close_requested(&self) -> bool {
+        self.imp().cancellable.cancel();
         self.imp().send_done(false, false);
         false
     }
@@ -340,6 +1121,11 @@ impl FileProps {
 
     #[template_callback]
     fn file_type_to_size_label_visible(&self, file_type: FilePropsType) -> bool {
+        file_type == FilePropsType::File || file_type == FilePropsType::Directory
+    }
+
+    #[template_callback]
+    fn file_type_to_checksum_visible(&self, file_type: FilePropsType) -> bool {
         file_type == FilePropsType::File
     }
 
@@ -32,19 +32,22 @@ use glib::translate::*;
 use glib::Properties;
 use gtk::{gdk, gio, glib, CompositeTemplate};
 use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::path::Path;
 use std::sync::OnceLock;
 
 use crate::{
     bookmarks_box::BookmarksBox,
     config::LOG_DOMAIN,
     dir_stack::DirStack,
-    dir_view::DirView,
+    dir_view::{DirView, DisplayMode, ViewMode, ICON_SIZES},
     path_bar::PathBar,
     places_box::PlacesBox,
+    preview_pane::PreviewPane,
     util::{self, stateful_action},
 };
 
-const ICON_SIZES: &[u32] = &[32, 48, 64, 96, 128, 256];
 const ICON_SIZE_MIN: u32 = ICON_SIZES[0];
 const ICON_SIZE_MAX: u32 = ICON_SIZES[ICON_SIZES.len() - 1];
 
@@ -75,6 +78,21 @@ pub enum SortMode {
     /// Sort files by modification time (newest first when reversed).
     #[enum_value(nick = "mtime")]
     ModificationTime = 1,
+    /// Sort files by size (largest first when reversed).
+    #[enum_value(nick = "size")]
+    Size = 2,
+}
+
+/// What activating (double-tapping or pressing Enter on) a file does.
+#[derive(Debug, Copy, Clone, Default, PartialEq, gio::glib::Enum)]
+#[enum_type(name = "PfsActivateAction")]
+pub enum ActivateAction {
+    /// Select the file and accept the selection right away.
+    #[default]
+    Accept,
+    /// Only select the file so the embedder can confirm the selection
+    /// on its own.
+    Select,
 }
 
 /// Implementation details for [`FileSelector`].
@@ -88,6 +106,9 @@ pub mod imp {
         #[template_child]
         pub dir_view: TemplateChild<DirView>,
 
+        #[template_child]
+        pub preview_pane: TemplateChild<PreviewPane>,
+
         #[template_child]
         pub bookmarks_box: TemplateChild<BookmarksBox>,
 
@@ -121,10 +142,16 @@ pub mod imp {
         #[template_child]
         pub search_entry: TemplateChild<gtk::SearchEntry>,
 
+        #[template_child]
+        pub filters_dropdown: TemplateChild<gtk::DropDown>,
+
         pub(super) choices_actions: RefCell<Option<gio::SimpleActionGroup>>,
 
         pub(super) settings: RefCell<Option<gio::Settings>>,
 
+        pub(super) all_files: RefCell<Option<gtk::FileFilter>>,
+        pub(super) updating_filters: Cell<bool>,
+
         #[property(set, get)]
         pub done: Cell<bool>,
 
@@ -142,7 +169,7 @@ pub mod imp {
         pub directory: Cell<bool>,
 
         // The filters
-        #[property(get, set, construct)]
+        #[property(get, set = Self::set_filters, construct)]
         pub filters: RefCell<Option<gio::ListModel>>,
 
         // Position in filters that is currently selected
@@ -151,7 +178,7 @@ pub mod imp {
         pub current_filter: Cell<u32>,
 
         // The current folder to open
-        #[property(get, set)]
+        #[property(get, set, construct)]
         pub current_folder: RefCell<Option<gio::File>>,
 
         // The file name (basename) when saving a file
@@ -177,6 +204,56 @@ pub mod imp {
         // Whether to close the window after a selection
         #[property(get, set, construct, default = true)]
         pub close_on_done: Cell<bool>,
+
+        // Whether tapping a folder selects it instead of entering it
+        #[property(get, set)]
+        pub select_directories: Cell<bool>,
+
+        // Whether to offer an "All Files" entry in the filter chooser
+        #[property(get, set = Self::set_all_files_filter, construct, default = true)]
+        pub all_files_filter: Cell<bool>,
+
+        // Additional entries appended to the items' context menu
+        #[property(get, set, nullable)]
+        pub item_menu: RefCell<Option<gio::MenuModel>>,
+
+        // Whether to store the current folder and reopen it next time
+        #[property(get, set, construct)]
+        pub remember_folder: Cell<bool>,
+
+        // Whether to show a preview of the selected file on wide screens
+        #[property(get, set = Self::set_show_preview, explicit_notify)]
+        pub show_preview: Cell<bool>,
+
+        // Whether to show the number of items and selected items
+        #[property(get, set)]
+        pub show_status: Cell<bool>,
+
+        // Whether the current folder can be chosen instead of a file
+        // when opening files
+        #[property(get, set)]
+        pub accept_folders: Cell<bool>,
+
+        // The current folder got chosen rather than a file
+        pub folder_chosen: Cell<bool>,
+
+        // Whether activating a file accepts or just selects it
+        #[property(get, set, builder(ActivateAction::default()))]
+        pub file_activation: Cell<ActivateAction>,
+
+        // Whether there's a previous folder to go back to
+        #[property(get, explicit_notify)]
+        pub can_go_back: Cell<bool>,
+
+        // Whether there's a next folder to go forward to
+        #[property(get, explicit_notify)]
+        pub can_go_forward: Cell<bool>,
+
+        // The files shown in the current folder along with their
+        // selection as `gio::FileInfo`s. This is a live model that
+        // follows folder changes, filtering and sorting.
+        #[property(get = Self::get_model)]
+        pub model: PhantomData<gtk::SelectionModel>,
     }
 
     #[glib::object_subclass]
@@ -193,12 +270,212 @@ pub mod imp {
                 file_selector.imp().on_accept_clicked();
             });
 
+            klass.install_action("file-selector.trash", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.trash_selected();
+            });
+
+            klass.install_action("file-selector.refresh", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.reload();
+            });
+
+            klass.install_action(
+                "file-selector.edit-location",
+                None,
+                move |file_selector, _, _| {
+                    // The path bar lives in the bottom sheet
+                    file_selector.imp().bottom_sheet.set_open(true);
+                    file_selector.imp().path_bar.start_editing();
+                },
+            );
+
+            klass.install_action(
+                "file-selector.clear-filters",
+                None,
+                move |file_selector, _, _| {
+                    file_selector.imp().search_entry.set_text("");
+                    file_selector.imp().dir_view.clear_filters();
+                },
+            );
+
+            klass.install_action("file-selector.copy", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.copy_selected();
+            });
+
+            klass.install_action("file-selector.undo", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.undo();
+            });
+
+            klass.install_action("file-selector.cut", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.cut_selected();
+            });
+
+            klass.install_action("file-selector.paste", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.paste();
+            });
+
+            klass.install_action(
+                "file-selector.new-folder",
+                None,
+                move |file_selector, _, _| {
+                    file_selector.imp().dir_view.create_folder();
+                },
+            );
+
+            klass.install_action(
+                "file-selector.choose-folder",
+                None,
+                move |file_selector, _, _| {
+                    file_selector.choose_current_folder();
+                },
+            );
+
+            klass.install_action(
+                "file-selector.bookmark",
+                None,
+                move |file_selector, _, _| {
+                    if let Some(folder) = file_selector.current_folder() {
+                        file_selector.add_bookmark(&folder.uri());
+                    }
+                },
+            );
+
+            klass.install_action(
+                "file-selector.select-all",
+                None,
+                move |file_selector, _, _| {
+                    file_selector.imp().dir_view.select_all();
+                },
+            );
+
+            klass.install_action(
+                "file-selector.unselect-all",
+                None,
+                move |file_selector, _, _| {
+                    file_selector.imp().dir_view.unselect_all();
+                },
+            );
+
+            klass.install_action("file-selector.up", None, move |file_selector, _, _| {
+                file_selector.imp().dir_view.go_up();
+            });
+
+            klass.install_action("file-selector.back", None, move |file_selector, _, _| {
+                file_selector.imp().dir_stack.back();
+            });
+
+            klass.install_action("file-selector.forward", None, move |file_selector, _, _| {
+                file_selector.imp().dir_stack.forward();
+            });
+
             klass.set_accessible_role(gtk::AccessibleRole::Group);
 
-            klass.add_binding_action(
+            klass.add_binding(
                 gdk::Key::Escape,
                 gdk::ModifierType::NO_MODIFIER_MASK,
-                "window.close",
+                |file_selector| {
+                    // Leave the search first so a single Escape doesn't
+                    // dismiss the whole dialog
+                    if file_selector.imp().dir_view.display_mode() == DisplayMode::Search {
+                        file_selector.imp().search_entry.set_text("");
+                        return glib::Propagation::Stop;
+                    }
+
+                    if file_selector.activate_action("window.close", None).is_err() {
+                        return glib::Propagation::Proceed;
+                    }
+                    glib::Propagation::Stop
+                },
+            );
+            klass.add_binding_action(
+                gdk::Key::F5,
+                gdk::ModifierType::NO_MODIFIER_MASK,
+                "file-selector.refresh",
+            );
+            klass.add_binding_action(
+                gdk::Key::r,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.refresh",
+            );
+            klass.add_binding_action(
+                gdk::Key::l,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.edit-location",
+            );
+            klass.add_binding_action(
+                gdk::Key::a,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.select-all",
+            );
+            klass.add_binding_action(
+                gdk::Key::A,
+                gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK,
+                "file-selector.unselect-all",
+            );
+            klass.add_binding_action(
+                gdk::Key::c,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.copy",
+            );
+            klass.add_binding_action(
+                gdk::Key::Up,
+                gdk::ModifierType::ALT_MASK,
+                "file-selector.up",
+            );
+            klass.add_binding(
+                gdk::Key::BackSpace,
+                gdk::ModifierType::NO_MODIFIER_MASK,
+                |file_selector| {
+                    // Leave Backspace to e.g. the search entry
+                    let in_text = file_selector
+                        .focus()
+                        .is_some_and(|widget| widget.is::<gtk::Text>());
+                    if in_text || !file_selector.imp().dir_view.can_go_up() {
+                        return glib::Propagation::Proceed;
+                    }
+
+                    file_selector.imp().dir_view.go_up();
+                    glib::Propagation::Stop
+                },
+            );
+            klass.add_binding_action(
+                gdk::Key::z,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.undo",
+            );
+            klass.add_binding_action(
+                gdk::Key::x,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.cut",
+            );
+            klass.add_binding_action(
+                gdk::Key::v,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.paste",
+            );
+            klass.add_binding_action(
+                gdk::Key::h,
+                gdk::ModifierType::CONTROL_MASK,
+                "file-selector.show-hidden-files",
+            );
+            klass.add_binding_action(
+                gdk::Key::Left,
+                gdk::ModifierType::ALT_MASK,
+                "file-selector.back",
+            );
+            klass.add_binding_action(
+                gdk::Key::Back,
+                gdk::ModifierType::NO_MODIFIER_MASK,
+                "file-selector.back",
+            );
+            klass.add_binding_action(
+                gdk::Key::Right,
+                gdk::ModifierType::ALT_MASK,
+                "file-selector.forward",
+            );
+            klass.add_binding_action(
+                gdk::Key::Forward,
+                gdk::ModifierType::NO_MODIFIER_MASK,
+                "file-selector.forward",
             );
         }
 
@@ -214,15 +491,25 @@ pub mod imp {
 
             let obj = self.obj();
             obj.setup_gsettings();
+            obj.setup_remember_folder();
             obj.setup_gactions();
+            obj.setup_history();
+            obj.setup_choose_folder();
         }
 
         fn signals() -> &'static [Signal] {
             static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
             SIGNALS.get_or_init(|| {
-                vec![Signal::builder("done")
-                    .param_types([bool::static_type()])
-                    .build()]
+                vec![
+                    Signal::builder("done")
+                        .param_types([bool::static_type()])
+                        .build(),
+                    // Emitted with the folder's URI whenever a different
+                    // folder is shown, with an empty URI when it got cleared
+                    Signal::builder("folder-changed")
+                        .param_types([String::static_type()])
+                        .build(),
+                ]
             })
         }
     }
@@ -233,6 +520,10 @@ pub mod imp {
 
     #[gtk::template_callbacks]
     impl FileSelector {
+        fn get_model(&self) -> gtk::SelectionModel {
+            self.dir_view.model()
+        }
+
         pub(super) fn send_done(&self, success: bool, close: bool) {
             let obj = self.obj();
 
@@ -256,6 +547,7 @@ pub mod imp {
 
             self.current_filter.replace(pos);
             obj.notify_current_filter();
+            self.sync_filters_dropdown();
 
             let filters = obj.filters();
             let mut filter: Option<gtk::FileFilter> = None;
@@ -272,6 +564,73 @@ pub mod imp {
             self.dir_view.set_type_filter(filter);
         }
 
+        fn set_filters(&self, filters: Option<gio::ListModel>) {
+            *self.filters.borrow_mut() = filters;
+            self.update_filters_dropdown();
+        }
+
+        fn set_all_files_filter(&self, all_files_filter: bool) {
+            self.all_files_filter.replace(all_files_filter);
+            self.update_filters_dropdown();
+        }
+
+        fn n_filters(&self) -> u32 {
+            self.filters
+                .borrow()
+                .as_ref()
+                .map_or(0, |filters| filters.n_items())
+        }
+
+        fn update_filters_dropdown(&self) {
+            let store = gio::ListStore::new::<gtk::FileFilter>();
+
+            if let Some(filters) = self.filters.borrow().as_ref() {
+                for filter in filters.iter::<gtk::FileFilter>().flatten() {
+                    store.append(&filter);
+                }
+            }
+
+            let n_filters = store.n_items();
+            if n_filters > 0 && self.all_files_filter.get() {
+                let all_files = self
+                    .all_files
+                    .borrow_mut()
+                    .get_or_insert_with(|| {
+                        let filter = gtk::FileFilter::new();
+                        filter.set_name(Some(&gettextrs::gettext("All Files")));
+                        filter.add_pattern("*");
+                        filter
+                    })
+                    .clone();
+                store.append(&all_files);
+            }
+
+            self.updating_filters.replace(true);
+            self.filters_dropdown.set_model(Some(&store));
+            self.updating_filters.replace(false);
+            self.filters_dropdown.set_visible(n_filters > 0);
+
+            self.sync_filters_dropdown();
+        }
+
+        fn sync_filters_dropdown(&self) {
+            let pos = self.current_filter.get();
+            let n_filters = self.n_filters();
+
+            // The "All Files" entry comes after the consumer's filters
+            let selected = if pos < n_filters {
+                pos
+            } else if self.all_files_filter.get() {
+                n_filters
+            } else {
+                gtk::INVALID_LIST_POSITION
+            };
+
+            self.updating_filters.replace(true);
+            self.filters_dropdown.set_selected(selected);
+            self.updating_filters.replace(false);
+        }
+
         fn set_filename(&self, filename: String) {
             let obj = self.obj();
 
@@ -303,6 +662,9 @@ pub mod imp {
                 self.search_bar
                     .set_key_capture_widget(Some(obj.upcast_ref::<gtk::Widget>()));
             }
+            // Typed keys go to the search bar when it captures them
+            self.dir_view
+                .set_type_ahead(mode != FileSelectorMode::OpenFile);
 
             *self.mode.borrow_mut() = mode;
             obj.notify_mode();
@@ -408,6 +770,7 @@ pub mod imp {
         #[template_callback]
         fn on_accept_clicked(&self) {
             glib::g_debug!(LOG_DOMAIN, "Selection done");
+            self.folder_chosen.set(false);
 
             if self.obj().mode() == FileSelectorMode::SaveFile {
                 let selected = self.obj().selected().unwrap();
@@ -431,8 +794,69 @@ pub mod imp {
             self.search_entry.set_text("");
         }
 
+        #[template_callback]
+        fn on_mouse_button_pressed(
+            &self,
+            _n_press: i32,
+            _x: f64,
+            _y: f64,
+            gesture: gtk::GestureClick,
+        ) {
+            let action = match gesture.current_button() {
+                8 => "file-selector.back",
+                9 => "file-selector.forward",
+                _ => return,
+            };
+
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            let _ = self.obj().activate_action(action, None);
+        }
+
+        fn set_show_preview(&self, show_preview: bool) {
+            if self.show_preview.get() == show_preview {
+                return;
+            }
+
+            self.show_preview.set(show_preview);
+            self.update_preview();
+            self.obj().notify_show_preview();
+        }
+
+        fn update_preview(&self) {
+            let info = if self.show_preview.get() && self.dir_view.has_selection() {
+                self.dir_view.selected_info()
+            } else {
+                None
+            };
+            self.preview_pane.set_fileinfo(info.as_ref());
+        }
+
+        #[template_callback]
+        fn on_folder_changed(&self, uri: String) {
+            self.obj().emit_by_name::<()>("folder-changed", &[&uri]);
+        }
+
+        #[template_callback]
+        fn on_has_selection_changed(&self) {
+            self.update_preview();
+        }
+
+        #[template_callback]
+        fn on_icon_size_changed(&self) {
+            self.obj().update_icon_size_action_sensitivity();
+        }
+
+        #[template_callback]
+        pub(super) fn on_can_paste_changed(&self) {
+            let can_paste = self.dir_view.can_paste();
+            self.obj()
+                .action_set_enabled("file-selector.paste", can_paste);
+        }
+
         #[template_callback]
         fn on_new_filename(&self, filename: String) {
+            self.update_preview();
+
             if self.obj().mode() != FileSelectorMode::SaveFile {
                 return;
             }
@@ -483,20 +907,39 @@ pub mod imp {
         }
 
         #[template_callback]
-        fn on_bookmark_clicked(&self, button: &gtk::Button) {
+        fn on_bookmark_clicked(&self) {
             let Some(file) = self.obj().current_folder() else {
                 return;
             };
 
+            // The button's icon follows the bookmarks
             let uri = file.uri();
-            let icon_name = if self.bookmarks_box.is_bookmark(&uri) {
+            if self.bookmarks_box.is_bookmark(&uri) {
                 self.bookmarks_box.del_bookmark(&uri);
-                "bookmark-outline-symbolic"
             } else {
                 self.bookmarks_box.add_bookmark(&uri);
-                "bookmark-filled-symbolic"
-            };
-            button.set_icon_name(icon_name);
+            }
+        }
+
+        #[template_callback]
+        fn sort_mode_to_label(&self, mode: SortMode) -> String {
+            match mode {
+                // Translators: Files are sorted by this
+                SortMode::DisplayName => gettextrs::gettext("Name"),
+                // Translators: Files are sorted by this
+                SortMode::ModificationTime => gettextrs::gettext("Modified"),
+                // Translators: Files are sorted by this
+                SortMode::Size => gettextrs::gettext("Size"),
+            }
+        }
+
+        #[template_callback]
+        fn reversed_to_sort_icon(&self, reversed: bool) -> &'static str {
+            if reversed {
+                "view-sort-descending-symbolic"
+            } else {
+                "view-sort-ascending-symbolic"
+            }
         }
 
         #[template_callback]
@@ -509,30 +952,38 @@ pub mod imp {
         }
 
         #[template_callback]
-        fn n_items_to_visible(&self) -> bool {
-            let Some(filters) = self.obj().filters() else {
-                return false;
+        fn on_filter_selected(&self) {
+            if self.updating_filters.get() {
+                return;
+            }
+
+            let selected = self.filters_dropdown.selected();
+            let pos = if selected < self.n_filters() {
+                selected
+            } else {
+                gtk::INVALID_LIST_POSITION
             };
-            filters.n_items() > 0
+            self.obj().set_current_filter(pos);
         }
 
         #[template_callback]
-        fn filters_to_menu_model(&self) -> Option<gio::MenuModel> {
-            let filters = self.obj().filters()?;
-            let menu = gio::Menu::new();
-            let mut pos = 0;
-            for item in &filters {
-                let item = item.unwrap();
-                let Some(filter) = item.downcast_ref::<gtk::FileFilter>() else {
-                    continue;
-                };
-                let name = filter.name().unwrap_or("Unknown filter".into());
-                let action = format!("file-selector.set-filter::{pos}");
-                menu.insert(pos, Some(&name), Some(&action));
-                pos += 1;
+        fn status_text(&self, n_items: u32, selection_count: u32) -> String {
+            let items = gettextrs::ngettext("{} item", "{} items", n_items).replacen(
+                "{}",
+                &n_items.to_string(),
+                1,
+            );
+
+            if selection_count < 2 {
+                return items;
             }
 
-            Some(menu.into())
+            let selected = gettextrs::ngettext("{} selected", "{} selected", selection_count)
+                .replacen("{}", &selection_count.to_string(), 1);
+            // Translators: The number of items and selected items in the status line
+            gettextrs::gettext("{items}, {selected}")
+                .replacen("{items}", &items, 1)
+                .replacen("{selected}", &selected, 1)
         }
 
         #[template_callback]
@@ -620,6 +1071,41 @@ impl FileSelector {
         *self.imp().settings.borrow_mut() = Some(settings);
     }
 
+    fn setup_remember_folder(&self) {
+        let Some(settings) = self.imp().settings.borrow().clone() else {
+            return;
+        };
+
+        if self.remember_folder() && self.current_folder().is_none() {
+            let uri = settings.string("last-folder");
+            let folder = Some(uri)
+                .filter(|uri| !uri.is_empty())
+                .map(|uri| gio::File::for_uri(&uri))
+                .filter(|folder| folder.query_exists(None::<&gio::Cancellable>))
+                .unwrap_or_else(|| gio::File::for_path(glib::home_dir()));
+
+            glib::g_debug!(LOG_DOMAIN, "Restoring folder {:#?}", folder.uri());
+            self.set_current_folder(folder);
+        }
+
+        self.connect_current_folder_notify(move |this| {
+            if !this.remember_folder() {
+                return;
+            }
+
+            let Some(folder) = this.current_folder() else {
+                return;
+            };
+
+            // Don't restore into e.g. recent:///
+            if !util::is_valid_folder(Some(&folder)) {
+                return;
+            }
+
+            let _ = settings.set_string("last-folder", &folder.uri());
+        });
+    }
+
     fn set_sort_mode(&self, name: &str, reversed: bool) {
         let enum_type = glib::EnumClass::with_type(SortMode::static_type()).unwrap();
         let mode = enum_type
@@ -637,18 +1123,16 @@ impl FileSelector {
     }
 
     fn update_icon_size_action_sensitivity(&self) {
-        let settings_binding = self.imp().settings.borrow();
-        if let Some(settings) = settings_binding.as_ref() {
-            let current_size = settings.uint("icon-size");
-            self.action_set_enabled(
-                "file-selector.increase-icon-size",
-                current_size < ICON_SIZE_MAX,
-            );
-            self.action_set_enabled(
-                "file-selector.decrease-icon-size",
-                current_size > ICON_SIZE_MIN,
-            );
-        }
+        // The dir view also tracks size changes done via pinch gestures
+        let current_size = self.imp().dir_view.icon_size();
+        self.action_set_enabled(
+            "file-selector.increase-icon-size",
+            current_size < ICON_SIZE_MAX,
+        );
+        self.action_set_enabled(
+            "file-selector.decrease-icon-size",
+            current_size > ICON_SIZE_MIN,
+        );
     }
 
     fn change_icon_size(&self, increase: bool) {
@@ -675,17 +1159,43 @@ impl FileSelector {
             if new_size != current_size {
                 glib::g_debug!(LOG_DOMAIN, "Icon size changed to {new_size}");
                 let _ = settings.set_uint("icon-size", new_size);
-                self.update_icon_size_action_sensitivity();
             }
         }
     }
 
+    fn can_choose_folder(&self) -> bool {
+        self.accept_folders()
+            && self.mode() == FileSelectorMode::OpenFile
+            && !self.directory()
+            && util::is_valid_folder(self.current_folder().as_ref())
+    }
+
+    fn setup_choose_folder(&self) {
+        for name in ["accept-folders", "mode", "directory", "current-folder"] {
+            self.connect_notify_local(Some(name), |this, _| {
+                this.action_set_enabled("file-selector.choose-folder", this.can_choose_folder());
+            });
+        }
+        self.action_set_enabled("file-selector.choose-folder", self.can_choose_folder());
+    }
+
+    // Accepts the current folder as if it was a selected file
+    fn choose_current_folder(&self) {
+        if !self.can_choose_folder() {
+            return;
+        }
+
+        glib::g_debug!(LOG_DOMAIN, "Folder chosen");
+        self.imp().folder_chosen.set(true);
+        self.imp().send_done(true, true);
+    }
+
     fn setup_gactions(&self) {
         let actions = gio::SimpleActionGroup::new();
         stateful_action!(
             actions,
             "show-hidden-files",
-            false,
+            self.imp().dir_view.show_hidden(),
             glib::clone!(
                 #[weak(rename_to = this)]
                 self,
@@ -699,6 +1209,23 @@ impl FileSelector {
                 }
             )
         );
+        stateful_action!(
+            actions,
+            "show-details",
+            self.imp().dir_view.show_details(),
+            glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move |action, _| {
+                    let state = action.state().unwrap();
+                    let action_state: bool = state.get().unwrap();
+                    let show_details = !action_state;
+                    action.set_state(&show_details.to_variant());
+
+                    this.imp().dir_view.get().set_show_details(show_details);
+                }
+            )
+        );
 
         let binding = self.imp().settings.borrow();
         let (sort_by_value, reversed) = if let Some(settings) = binding.as_ref() {
@@ -754,6 +1281,40 @@ impl FileSelector {
 
         self.insert_action_group("file-selector", Some(&actions));
 
+        // Keep the sort menu in sync when sorting is changed programmatically
+        let sort_action = actions.lookup_action("sort").unwrap();
+        for name in ["sort-mode", "reversed"] {
+            self.imp().dir_view.connect_notify_local(
+                Some(name),
+                glib::clone!(
+                    #[weak]
+                    sort_action,
+                    move |dir_view, _| {
+                        let enum_type =
+                            glib::EnumClass::with_type(SortMode::static_type()).unwrap();
+                        let nick = enum_type.value(dir_view.sort_mode() as i32).unwrap().nick();
+                        sort_action.change_state(&(nick, dir_view.reversed()).to_variant());
+                    }
+                ),
+            );
+        }
+
+        // Keep action in sync with `show-hidden` as it can change via GSettings
+        let hidden_action = actions.lookup_action("show-hidden-files").unwrap();
+        self.imp()
+            .dir_view
+            .bind_property("show-hidden", &hidden_action, "state")
+            .transform_to(|_, show_hidden: bool| Some(show_hidden.to_variant()))
+            .build();
+
+        // Same for `show-details`
+        let details_action = actions.lookup_action("show-details").unwrap();
+        self.imp()
+            .dir_view
+            .bind_property("show-details", &details_action, "state")
+            .transform_to(|_, show_details: bool| Some(show_details.to_variant()))
+            .build();
+
         // Keep `current-filter` in sync with action
         let filter_action = actions.lookup_action("set-filter").unwrap();
         self.bind_property("current-filter", &filter_action, "state")
@@ -781,6 +1342,83 @@ impl FileSelector {
         }
 
         self.update_icon_size_action_sensitivity();
+        self.imp().on_can_paste_changed();
+
+        let dir_view = self.imp().dir_view.get();
+        let view_mode = match dir_view.view_mode() {
+            ViewMode::Grid => "grid",
+            ViewMode::List => "list",
+        };
+        stateful_action!(
+            actions,
+            "view-mode",
+            Some("".to_variant().type_()),
+            view_mode,
+            glib::clone!(
+                #[weak]
+                dir_view,
+                move |_, param| {
+                    let param = param.unwrap();
+                    let view_mode = match param.get::<String>().unwrap().as_str() {
+                        "list" => ViewMode::List,
+                        _ => ViewMode::Grid,
+                    };
+                    dir_view.set_view_mode(view_mode);
+                }
+            )
+        );
+
+        // Keep action in sync with `view-mode` (e.g. when changed via GSettings)
+        let view_mode_action = actions.lookup_action("view-mode").unwrap();
+        dir_view
+            .bind_property("view-mode", &view_mode_action, "state")
+            .sync_create()
+            .transform_to(|_, view_mode: ViewMode| {
+                let view_mode = match view_mode {
+                    ViewMode::Grid => "grid",
+                    ViewMode::List => "list",
+                };
+                Some(view_mode.to_variant())
+            })
+            .build();
+    }
+
+    fn setup_history(&self) {
+        let dir_stack = self.imp().dir_stack.get();
+
+        for name in ["can-go-back", "can-go-forward"] {
+            dir_stack.connect_notify_local(
+                Some(name),
+                glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_, _| this.sync_history()
+                ),
+            );
+        }
+        self.connect_current_folder_notify(|this| this.sync_history());
+        self.sync_history();
+    }
+
+    fn sync_history(&self) {
+        let imp = self.imp();
+        let dir_stack = imp.dir_stack.get();
+
+        let can_go_back = dir_stack.can_go_back();
+        self.action_set_enabled("file-selector.back", can_go_back);
+        if imp.can_go_back.replace(can_go_back) != can_go_back {
+            self.notify_can_go_back();
+        }
+
+        let can_go_forward = dir_stack.can_go_forward();
+        self.action_set_enabled("file-selector.forward", can_go_forward);
+        if imp.can_go_forward.replace(can_go_forward) != can_go_forward {
+            self.notify_can_go_forward();
+        }
+
+        // Nothing above the root
+        let can_go_up = imp.dir_view.can_go_up();
+        self.action_set_enabled("file-selector.up", can_go_up);
     }
 
     fn confirm_overwrite(&self, file: &gio::File) {
@@ -825,6 +1463,11 @@ impl FileSelector {
     ///
     /// Returns `None` if no selection has been made.
     pub fn selected(&self) -> Option<Vec<String>> {
+        if self.imp().folder_chosen.get() {
+            let folder = self.current_folder()?;
+            return Some(vec![folder.uri().to_string()]);
+        }
+
         let items = self.imp().dir_view.get().selected();
 
         if self.mode() == FileSelectorMode::SaveFile {
@@ -847,6 +1490,98 @@ impl FileSelector {
         self.set_current_folder(file);
     }
 
+    /// Opens the folder given by `uri`.
+    ///
+    /// Returns `false` and leaves the current folder unchanged if `uri`
+    /// doesn't refer to a usable (local and existing) directory.
+    pub fn set_folder_uri(&self, uri: &str) -> bool {
+        self.set_folder_checked(gio::File::for_uri(uri))
+    }
+
+    /// Opens the folder at `path`.
+    ///
+    /// Returns `false` and leaves the current folder unchanged if `path`
+    /// isn't an existing directory.
+    pub fn set_folder_path(&self, path: &Path) -> bool {
+        self.set_folder_checked(gio::File::for_path(path))
+    }
+
+    fn set_folder_checked(&self, folder: gio::File) -> bool {
+        if !util::is_valid_folder(Some(&folder)) {
+            return false;
+        }
+
+        let file_type =
+            folder.query_file_type(gio::FileQueryInfoFlags::NONE, None::<&gio::Cancellable>);
+        if file_type != gio::FileType::Directory {
+            glib::g_debug!(LOG_DOMAIN, "Not a directory: {}", folder.uri());
+            return false;
+        }
+
+        self.set_current_folder(folder);
+        true
+    }
+
+    /// Appends `filter` to [`filters`](Self::filters) so users can pick it
+    /// in the filter chooser.
+    ///
+    /// If no filter is active yet, the new filter becomes the current one.
+    pub fn add_filter(&self, filter: &gtk::FileFilter) {
+        // Build a new model so `filters` gets notified
+        let store = gio::ListStore::new::<gtk::FileFilter>();
+        if let Some(filters) = self.filters() {
+            for item in filters.iter::<gtk::FileFilter>().flatten() {
+                store.append(&item);
+            }
+        }
+        store.append(filter);
+        self.set_filters(store.upcast_ref::<gio::ListModel>());
+
+        if self.current_filter() == gtk::INVALID_LIST_POSITION {
+            self.set_current_filter(store.n_items() - 1);
+        }
+    }
+
+    /// Adds a filter named `name` that shows files with one of the given
+    /// `extensions` (e.g. `&["jpg", "png"]`) and appends it to
+    /// [`filters`](Self::filters). Folders always stay browsable.
+    ///
+    /// If no filter is active yet, the new filter becomes the current one.
+    ///
+    /// ```no_run
+    /// use pfs::file_selector::FileSelector;
+    ///
+    /// let selector = FileSelector::new();
+    /// selector.add_filter_for_extensions("Images", &["jpg", "png"]);
+    /// ```
+    pub fn add_filter_for_extensions(&self, name: &str, extensions: &[&str]) -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+
+        filter.set_name(Some(name));
+        for extension in extensions {
+            let suffix = extension.trim_start_matches('*').trim_start_matches('.');
+            if !suffix.is_empty() {
+                filter.add_suffix(suffix);
+            }
+        }
+
+        self.add_filter(&filter);
+        filter
+    }
+
+    /// Returns the filter that is currently applied to the displayed files.
+    ///
+    /// Returns `None` if all files are shown.
+    pub fn active_filter(&self) -> Option<gtk::FileFilter> {
+        self.imp().dir_view.type_filter()
+    }
+
+    /// Clears the search term as well as the name, modification time and
+    /// size filters. The active filter and a custom filter are kept.
+    pub fn clear_filters(&self) {
+        self.imp().dir_view.clear_filters();
+    }
+
     /// Programmatically selects a file in the current directory view.
     ///
     /// The file must be in the currently displayed directory.
@@ -854,6 +1589,52 @@ impl FileSelector {
         self.imp().dir_view.select_item(item);
     }
 
+    /// Selects `file` and scrolls it into view. If the folder is still
+    /// loading the file gets selected once loading finished.
+    ///
+    /// `file` must be in the current folder.
+    pub fn select_file(&self, file: &gio::File) {
+        self.imp().dir_view.select_file(file);
+    }
+
+    /// Selects all files currently shown. Does nothing unless the mode
+    /// allows selecting multiple files.
+    pub fn select_all(&self) {
+        self.imp().dir_view.select_all();
+    }
+
+    /// Unselects all files. Does nothing unless the mode allows selecting
+    /// multiple files.
+    pub fn unselect_all(&self) {
+        self.imp().dir_view.unselect_all();
+    }
+
+    /// Sorts the displayed files with `func` instead of the built-in sort
+    /// modes. Directories are still grouped according to the
+    /// `directories-first-mode` setting.
+    pub fn set_custom_sorter(
+        &self,
+        func: impl Fn(&gio::FileInfo, &gio::FileInfo) -> Ordering + 'static,
+    ) {
+        self.imp().dir_view.set_custom_sorter(func);
+    }
+
+    /// Removes a sort function set via [`Self::set_custom_sorter`].
+    pub fn clear_custom_sorter(&self) {
+        self.imp().dir_view.clear_custom_sorter();
+    }
+
+    /// Only shows files for which `func` returns `true`. This is applied
+    /// in addition to the active filter and the search term.
+    pub fn set_custom_filter(&self, func: impl Fn(&gio::FileInfo) -> bool + 'static) {
+        self.imp().dir_view.set_custom_filter(func);
+    }
+
+    /// Removes a filter function set via [`Self::set_custom_filter`].
+    pub fn clear_custom_filter(&self) {
+        self.imp().dir_view.clear_custom_filter();
+    }
+
     /// Displays a toast notification in the file selector.
     pub fn show_toast(&self, toast: adw::Toast) {
         self.imp().toast_overlay.add_toast(toast);
@@ -930,6 +1711,18 @@ impl FileSelectorBuilder {
         self
     }
 
+    /// Sets the `select-directories` property.
+    ///
+    /// When `true` and selecting a directory, tapping a subfolder selects
+    /// it instead of entering it. Activating the subfolder enters it.
+    /// Otherwise the current folder is the selection.
+    pub fn select_directories(mut self, select_directories: bool) -> Self {
+        self.builder = self
+            .builder
+            .property("select-directories", select_directories);
+        self
+    }
+
     /// Sets the `filters` property, a [`gio::ListModel`] of
     /// [`gtk::FileFilter`]s to filter the visible files by.
     pub fn filters(mut self, filters: gio::ListModel) -> Self {
@@ -945,6 +1738,15 @@ impl FileSelectorBuilder {
         self
     }
 
+    /// Sets the `all-files-filter` property.
+    ///
+    /// When `true` (the default), the filter chooser offers an additional
+    /// "All Files" entry that shows every file.
+    pub fn all_files_filter(mut self, all_files_filter: bool) -> Self {
+        self.builder = self.builder.property("all-files-filter", all_files_filter);
+        self
+    }
+
     /// Sets the `current-folder` property, the folder the selector displays
     /// initially.
     pub fn current_folder(mut self, folder: gio::File) -> Self {
@@ -976,6 +1778,64 @@ impl FileSelectorBuilder {
         self
     }
 
+    /// Sets the `remember-folder` property.
+    ///
+    /// When `true`, the last visited folder is stored and opened again
+    /// by the next selector unless a [`current_folder`](Self::current_folder)
+    /// is given.
+    pub fn remember_folder(mut self, remember_folder: bool) -> Self {
+        self.builder = self.builder.property("remember-folder", remember_folder);
+        self
+    }
+
+    /// Sets the `show-preview` property.
+    ///
+    /// When `true`, a preview of the selected file is shown next to the
+    /// files on wide screens. It's hidden on narrow (phone) layouts.
+    pub fn show_preview(mut self, show_preview: bool) -> Self {
+        self.builder = self.builder.property("show-preview", show_preview);
+        self
+    }
+
+    /// Sets the `show-status` property.
+    ///
+    /// When `true`, a status line below the files shows how many items
+    /// are visible and how many of them are selected.
+    pub fn show_status(mut self, show_status: bool) -> Self {
+        self.builder = self.builder.property("show-status", show_status);
+        self
+    }
+
+    /// Sets the `accept-folders` property.
+    ///
+    /// When `true` and opening files, the menu offers to select the
+    /// current folder instead of a file. [`FileSelector::selected`] then
+    /// returns the folder's URI.
+    pub fn accept_folders(mut self, accept_folders: bool) -> Self {
+        self.builder = self.builder.property("accept-folders", accept_folders);
+        self
+    }
+
+    /// Sets the `file-activation` property.
+    ///
+    /// With [`ActivateAction::Select`], activating a file only selects it
+    /// instead of accepting the selection. Activating a folder still
+    /// enters it.
+    pub fn file_activation(mut self, file_activation: ActivateAction) -> Self {
+        self.builder = self.builder.property("file-activation", file_activation);
+        self
+    }
+
+    /// Sets the `item-menu` property, a [`gio::MenuModel`] that is appended
+    /// as an additional section to the context menu of files and folders.
+    ///
+    /// The clicked file is selected before the menu pops up so actions can
+    /// use [`FileSelector::selected`] to find out which file to act on.
+    pub fn item_menu(mut self, item_menu: &impl IsA<gio::MenuModel>) -> Self {
+        self.builder = self.builder.property("item-menu", item_menu.as_ref());
+        self
+    }
+
     /// Build the [`FileSelector`].
     pub fn build(self) -> FileSelector {
         self.builder.build()
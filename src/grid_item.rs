@@ -9,13 +9,20 @@
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use glib::Properties;
-use gtk::{gio, glib, CompositeTemplate};
-use std::cell::{Cell, RefCell};
+use gtk::{gdk, gio, glib, CompositeTemplate};
+use std::cell::{Cell, OnceCell, RefCell};
 
 use crate::{
-    config::LOG_DOMAIN, dir_view::ThumbnailMode, file_props::FileProps, file_selector::FileSelector,
+    config::LOG_DOMAIN,
+    dir_view::{DirView, ItemDetails, ThumbnailMode},
+    file_props::FileProps,
+    file_selector::FileSelector,
+    open, util,
 };
 
+// How long it takes for a thumbnail to blend in
+const THUMBNAIL_FADE_IN_MS: u32 = 250;
+
 mod imp {
     use super::*;
 
@@ -23,12 +30,39 @@ mod imp {
     #[template(resource = "/mobi/phosh/FileSelector/grid-item.ui")]
     #[properties(wrapper_type = super::GridItem)]
     pub struct GridItem {
+        #[template_child]
+        pub item_box: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub icon_overlay: TemplateChild<gtk::Overlay>,
+
         #[template_child]
         pub icon: TemplateChild<gtk::Image>,
 
         #[template_child]
         pub label: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub symlink_emblem: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub media_badge: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub media_badge_icon: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub media_badge_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub details_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub name_stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub rename_entry: TemplateChild<gtk::Entry>,
+
         #[template_child]
         pub context_menu: TemplateChild<gtk::PopoverMenu>,
 
@@ -43,6 +77,24 @@ mod imp {
 
         #[property(get, set = Self::set_thumbnail_mode, builder(ThumbnailMode::default()))]
         pub thumbnail_mode: RefCell<ThumbnailMode>,
+
+        // Icon and name side by side (e.g. for list rows)
+        #[property(get, set = Self::set_compact, explicit_notify)]
+        pub compact: Cell<bool>,
+
+        // Whether to show a line with details below the name
+        #[property(get, set = Self::set_show_details, explicit_notify)]
+        pub show_details: Cell<bool>,
+
+        // The details to show
+        #[property(get, set = Self::set_details, explicit_notify, builder(ItemDetails::default()))]
+        pub details: Cell<ItemDetails>,
+
+        // A thumbnail was requested but didn't arrive yet
+        #[property(get, set = Self::set_thumbnail_pending, explicit_notify)]
+        pub thumbnail_pending: Cell<bool>,
+
+        pub fade_in: OnceCell<adw::TimedAnimation>,
     }
 
     #[glib::object_subclass]
@@ -54,13 +106,32 @@ mod imp {
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
             klass.bind_template_instance_callbacks();
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
 
+            klass.install_action("grid-item.open", None, move |item, _, _| {
+                item.open();
+            });
             klass.install_action("grid-item.show-property", None, move |item, _, _| {
                 item.show_properties();
             });
+            klass.install_action("grid-item.rename", None, move |item, _, _| {
+                item.start_rename();
+            });
+            klass.install_action("grid-item.trash", None, move |item, _, _| {
+                item.trash();
+            });
+            klass.install_action("grid-item.copy", None, move |item, _, _| {
+                item.copy_file_to_clipboard(false);
+            });
+            klass.install_action("grid-item.cut", None, move |item, _, _| {
+                item.copy_file_to_clipboard(true);
+            });
             klass.install_action("grid-item.copy-name", None, move |item, _, _| {
                 item.copy_to_clipboard();
             });
+            klass.install_action("grid-item.copy-path", None, move |item, _, _| {
+                item.copy_path_to_clipboard();
+            });
             klass.install_action("grid-item.add-bookmark", None, move |item, _, _| {
                 item.add_bookmark();
             });
@@ -83,12 +154,9 @@ mod imp {
                 return;
             };
             if *self.thumbnail_mode.borrow() != ThumbnailMode::Never {
-                if let Some(path) = info.attribute_byte_string("thumbnail::path") {
-                    let valid = info.boolean("thumbnail::is-valid");
-                    if valid {
-                        self.icon.set_from_file(Some(path));
-                        have_thumbnail = true;
-                    }
+                if let Some(path) = util::cached_thumbnail_path(info) {
+                    self.icon.set_from_file(Some(path));
+                    have_thumbnail = true;
                 }
             }
 
@@ -99,11 +167,170 @@ mod imp {
             }
         }
 
+        fn update_media_badge(&self, info: &gio::FileInfo) {
+            let content_type = info.content_type().unwrap_or_default();
+            let icon_name = if content_type.starts_with("video/") {
+                "video-x-generic-symbolic"
+            } else if content_type.starts_with("audio/") {
+                "audio-x-generic-symbolic"
+            } else {
+                self.media_badge.set_visible(false);
+                return;
+            };
+
+            let duration = info
+                .attribute_as_string(util::MEDIA_DURATION_ATTRIBUTE)
+                .and_then(|secs| secs.parse::<f64>().ok())
+                .filter(|secs| *secs > 0.0)
+                .map(|secs| util::format_duration(secs.round() as u64));
+
+            self.media_badge_icon.set_icon_name(Some(icon_name));
+            self.media_badge_label.set_visible(duration.is_some());
+            self.media_badge_label
+                .set_label(duration.as_deref().unwrap_or_default());
+            self.media_badge.set_visible(true);
+        }
+
+        fn update_symlink_emblem(&self, info: &gio::FileInfo) {
+            self.symlink_emblem.set_visible(info.is_symlink());
+        }
+
+        // The full name as it might be elided in the grid, plus some metadata
+        fn update_tooltip(&self, info: &gio::FileInfo) {
+            let mut lines = vec![info.display_name().to_string()];
+
+            if info.file_type() != gio::FileType::Directory {
+                lines.push(util::format_size(info.size() as u64).to_string());
+            }
+
+            let modified = info
+                .modification_date_time()
+                .and_then(|modified| modified.to_local().ok())
+                .and_then(|modified| modified.format("%x %R").ok());
+            if let Some(modified) = modified {
+                lines.push(modified.to_string());
+            }
+
+            if let Some(target) = info.is_symlink().then(|| info.symlink_target()).flatten() {
+                let target = target.to_string_lossy();
+                lines.push(gettextrs::gettext("Link to “{}”").replacen("{}", &target, 1));
+            }
+
+            self.obj().set_tooltip_text(Some(&lines.join("\n")));
+        }
+
+        // Size and/or modification time below the name
+        fn update_details(&self) {
+            let borrowed = self.fileinfo.borrow();
+            let Some(info) = borrowed.as_ref().filter(|_| self.show_details.get()) else {
+                self.details_label.set_visible(false);
+                return;
+            };
+
+            let details = self.details.get();
+            let mut parts = Vec::new();
+
+            if details != ItemDetails::Modified && info.file_type() != gio::FileType::Directory {
+                parts.push(util::format_size(info.size() as u64).to_string());
+            }
+
+            if details != ItemDetails::Size {
+                if let Some(modified) = info
+                    .modification_date_time()
+                    .and_then(|modified| util::format_relative_date_time(&modified))
+                {
+                    parts.push(modified);
+                }
+            }
+
+            self.details_label.set_label(&parts.join(" · "));
+            self.details_label.set_visible(!parts.is_empty());
+        }
+
+        // So screen readers announce more than just an image
+        fn update_accessible(&self, info: &gio::FileInfo) {
+            let name = info.display_name();
+            let content_type = info.content_type().unwrap_or_default();
+            let mut description = gio::content_type_get_description(&content_type).to_string();
+
+            if info.file_type() != gio::FileType::Directory {
+                let size = util::format_size(info.size() as u64);
+                description = gettextrs::gettext("{type}, {size}")
+                    .replacen("{type}", &description, 1)
+                    .replacen("{size}", &size, 1);
+            }
+
+            self.obj().update_property(&[
+                gtk::accessible::Property::Label(&name),
+                gtk::accessible::Property::Description(&description),
+            ]);
+        }
+
         fn set_fileinfo(&self, info: gio::FileInfo) {
             self.label.set_label(&info.display_name());
+            // The item might get reused while renaming
+            self.name_stack.set_visible_child_name("label");
+            // Don't fade in the icon of a recycled item
+            self.fade_in.get().unwrap().skip();
+            self.set_thumbnail_pending(false);
+            self.update_media_badge(&info);
+            self.update_symlink_emblem(&info);
+            self.update_tooltip(&info);
+            self.update_accessible(&info);
 
             *self.fileinfo.borrow_mut() = Some(info);
             self.update_image();
+            self.update_details();
+        }
+
+        fn set_show_details(&self, show_details: bool) {
+            if self.show_details.replace(show_details) == show_details {
+                return;
+            }
+
+            self.update_details();
+            self.obj().notify_show_details();
+        }
+
+        fn set_details(&self, details: ItemDetails) {
+            if self.details.replace(details) == details {
+                return;
+            }
+
+            self.update_details();
+            self.obj().notify_details();
+        }
+
+        fn set_compact(&self, compact: bool) {
+            if self.compact.get() == compact {
+                return;
+            }
+            self.compact.set(compact);
+
+            let (orientation, align, justify, lines) = if compact {
+                (
+                    gtk::Orientation::Horizontal,
+                    gtk::Align::Start,
+                    gtk::Justification::Left,
+                    1,
+                )
+            } else {
+                (
+                    gtk::Orientation::Vertical,
+                    gtk::Align::Center,
+                    gtk::Justification::Center,
+                    3,
+                )
+            };
+
+            self.item_box.set_orientation(orientation);
+            self.icon_overlay.set_hexpand(!compact);
+            self.label.set_halign(align);
+            self.label.set_hexpand(compact);
+            self.label.set_justify(justify);
+            self.label.set_lines(lines);
+            self.label.set_xalign(if compact { 0.0 } else { 0.5 });
+            self.obj().notify_compact();
         }
 
         fn set_thumbnail_mode(&self, mode: ThumbnailMode) {
@@ -112,8 +339,25 @@ mod imp {
             }
 
             self.thumbnail_mode.replace(mode);
+            if mode == ThumbnailMode::Never {
+                self.set_thumbnail_pending(false);
+            }
             self.update_image();
         }
+
+        fn set_thumbnail_pending(&self, pending: bool) {
+            if self.thumbnail_pending.get() == pending {
+                return;
+            }
+            self.thumbnail_pending.set(pending);
+
+            if pending {
+                self.icon.add_css_class("pfs-thumbnail-pending");
+            } else {
+                self.icon.remove_css_class("pfs-thumbnail-pending");
+            }
+            self.obj().notify_thumbnail_pending();
+        }
     }
 
     #[glib::derived_properties]
@@ -121,6 +365,17 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.obj().set_icon_size(32);
+
+            let target = adw::PropertyAnimationTarget::new(&*self.icon, "opacity");
+            let fade_in = adw::TimedAnimation::builder()
+                .widget(&*self.icon)
+                .target(&target)
+                .value_from(0.0)
+                .value_to(1.0)
+                .duration(THUMBNAIL_FADE_IN_MS)
+                .easing(adw::Easing::EaseOutCubic)
+                .build();
+            self.fade_in.set(fade_in).unwrap();
         }
 
         fn dispose(&self) {
@@ -155,9 +410,16 @@ impl GridItem {
     pub fn set_thumbnail(&self, path: String) {
         let imp = self.imp();
 
-        if *imp.thumbnail_mode.borrow() != ThumbnailMode::Never {
-            imp.icon.set_from_file(Some(path));
+        self.set_thumbnail_pending(false);
+        if *imp.thumbnail_mode.borrow() == ThumbnailMode::Never {
+            return;
         }
+
+        imp.icon.set_from_file(Some(path));
+        // Blend the thumbnail in rather than abruptly replacing the icon
+        let fade_in = imp.fade_in.get().unwrap();
+        fade_in.reset();
+        fade_in.play();
     }
 
     fn get_file_selector(&self) -> FileSelector {
@@ -166,6 +428,11 @@ impl GridItem {
             .expect("FileSelector must be at the root")
     }
 
+    fn get_dir_view(&self) -> Option<DirView> {
+        self.ancestor(DirView::static_type())
+            .and_downcast::<DirView>()
+    }
+
     fn get_file(&self) -> gio::File {
         let imp = self.imp();
         let fileinfo = imp.fileinfo.borrow();
@@ -176,17 +443,141 @@ impl GridItem {
             .unwrap()
     }
 
+    fn open(&self) {
+        let file = self.get_file();
+        let uri = file.uri();
+        let is_dir = self
+            .imp()
+            .fileinfo
+            .borrow()
+            .as_ref()
+            .is_some_and(|info| info.file_type() == gio::FileType::Directory);
+
+        if is_dir {
+            if let Some(dir_view) = self.get_dir_view() {
+                dir_view.emit_by_name::<()>("new-uri", &[&uri]);
+            }
+            return;
+        }
+
+        glib::g_debug!(LOG_DOMAIN, "Opening {uri}");
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                if let Err(err) = open::launch_uri(&uri).await {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to open {uri}: {err}");
+                    let msg = gettextrs::gettext("Failed to open {}").replacen("{}", &uri, 1);
+                    this.get_file_selector().show_toast(adw::Toast::new(&msg));
+                }
+            }
+        ));
+    }
+
     fn show_properties(&self) {
         let file = self.get_file();
         let uri = file.uri();
         glib::g_debug!(LOG_DOMAIN, "Showing properties for {uri}");
 
         let file_props = FileProps::builder().file(&file).build();
+        if let Some(dir_view) = self.get_dir_view() {
+            file_props.set_dir_view(&dir_view);
+        }
 
         file_props.set_transient_for(Some(&self.get_file_selector()));
         file_props.present();
     }
 
+    fn start_rename(&self) {
+        let imp = self.imp();
+        let Some(info) = imp.fileinfo.borrow().clone() else {
+            return;
+        };
+
+        let display_name = info.display_name();
+        imp.rename_entry.set_text(&display_name);
+        imp.name_stack.set_visible_child_name("rename");
+        imp.rename_entry.grab_focus();
+
+        // Preselect the name without the extension
+        let len = match display_name.rfind('.') {
+            Some(pos) if pos > 0 && info.file_type() != gio::FileType::Directory => {
+                display_name[..pos].chars().count()
+            }
+            _ => display_name.chars().count(),
+        };
+        imp.rename_entry.select_region(0, len as i32);
+    }
+
+    fn stop_rename(&self) {
+        let imp = self.imp();
+
+        imp.name_stack.set_visible_child_name("label");
+        imp.rename_entry.set_text("");
+    }
+
+    fn commit_rename(&self) {
+        let imp = self.imp();
+        let Some(info) = imp.fileinfo.borrow().clone() else {
+            return;
+        };
+
+        let new_name = imp.rename_entry.text().trim().to_string();
+        self.stop_rename();
+        if new_name.is_empty() || new_name == info.display_name() {
+            return;
+        }
+
+        let Some(dir_view) = self.get_dir_view() else {
+            return;
+        };
+        let file = self.get_file();
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let old_name = info.display_name();
+                if let Err(err) = dir_view.rename_file(&file, &old_name, &new_name).await {
+                    let msg = if err.matches(gio::IOErrorEnum::Exists) {
+                        gettextrs::gettext("A file named “{}” already exists")
+                    } else {
+                        gettextrs::gettext("Failed to rename to “{}”")
+                    }
+                    .replacen("{}", &new_name, 1);
+
+                    glib::g_warning!(LOG_DOMAIN, "Failed to rename {}: {err}", file.uri());
+                    this.get_file_selector().show_toast(adw::Toast::new(&msg));
+                }
+            }
+        ));
+    }
+
+    fn trash(&self) {
+        if let Some(dir_view) = self.get_dir_view() {
+            dir_view.trash_file(&self.get_file());
+        }
+    }
+
+    fn copy_file_to_clipboard(&self, cut: bool) {
+        let Some(dir_view) = self.get_dir_view() else {
+            return;
+        };
+        dir_view.copy_files(&[self.get_file()], cut);
+
+        let toast_message = if cut {
+            gettextrs::gettext("Cut to clipboard")
+        } else {
+            gettextrs::gettext("Copied to clipboard")
+        };
+        let toast = adw::Toast::builder()
+            .title(&toast_message)
+            .timeout(2)
+            .build();
+
+        self.get_file_selector().show_toast(toast);
+    }
+
     fn copy_to_clipboard(&self) {
         let imp = self.imp();
 
@@ -203,6 +594,24 @@ impl GridItem {
         self.get_file_selector().show_toast(toast);
     }
 
+    fn copy_path_to_clipboard(&self) {
+        let file = self.get_file();
+        let path = match file.path() {
+            Some(path) => path.to_string_lossy().to_string(),
+            None => file.uri().to_string(),
+        };
+
+        self.clipboard().set_text(&path);
+
+        let toast_message = gettextrs::gettext("Copied to clipboard");
+        let toast = adw::Toast::builder()
+            .title(&toast_message)
+            .timeout(2)
+            .build();
+
+        self.get_file_selector().show_toast(toast);
+    }
+
     fn add_bookmark(&self) {
         let file = self.get_file();
         let uri = file.uri();
@@ -260,15 +669,76 @@ impl GridItem {
                 add_bookmark = true;
             }
         }
+        self.action_set_enabled("grid-item.rename", file.path().is_some());
+        self.action_set_enabled("grid-item.trash", file.path().is_some());
+        self.action_set_enabled("grid-item.cut", file.path().is_some());
         self.action_set_enabled("grid-item.add-bookmark", add_bookmark);
         self.action_set_enabled("grid-item.del-bookmark", del_bookmark);
 
+        // Selecting a folder would enter it so only select files
+        if info.file_type() != gio::FileType::Directory {
+            if let Some(dir_view) = self.get_dir_view() {
+                dir_view.select_item(&file);
+            }
+        }
+
+        let menu = gio::Menu::new();
+        menu.append_section(None, &*imp.context_menu_model);
+        if let Some(item_menu) = fs.item_menu() {
+            menu.append_section(None, &item_menu);
+        }
+        popover.set_menu_model(Some(&menu));
+
         popover.unparent();
         popover.set_parent(self);
         popover.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
         popover.popup();
     }
 
+    #[template_callback]
+    fn on_rename_activate(&self) {
+        self.commit_rename();
+    }
+
+    #[template_callback]
+    fn on_rename_key_pressed(&self, keyval: gdk::Key) -> bool {
+        if keyval != gdk::Key::Escape {
+            return false;
+        }
+
+        self.stop_rename();
+        true
+    }
+
+    #[template_callback]
+    fn on_drag_prepare(&self, _x: f64, _y: f64) -> Option<gdk::ContentProvider> {
+        let imp = self.imp();
+
+        // Don't interfere with selecting text while renaming
+        if imp.name_stack.visible_child_name().as_deref() == Some("rename") {
+            return None;
+        }
+
+        let file = imp
+            .fileinfo
+            .borrow()
+            .as_ref()?
+            .attribute_object("standard::file")
+            .and_downcast::<gio::File>()?;
+
+        // GTK serializes file lists as text/uri-list for us
+        let file_list = gdk::FileList::from_array(&[file]);
+        Some(gdk::ContentProvider::for_value(&file_list.to_value()))
+    }
+
+    #[template_callback]
+    fn on_drag_begin(&self, _drag: gdk::Drag, source: gtk::DragSource) {
+        let icon = &*self.imp().icon;
+        let paintable = gtk::WidgetPaintable::new(Some(icon));
+
+        source.set_icon(Some(&paintable), icon.width() / 2, icon.height() / 2);
+    }
+
     #[template_callback]
     fn on_long_press_pressed(&self, x: f64, y: f64) {
         self.show_context_menu(x, y);
@@ -6,9 +6,11 @@
  * Author: Guido Günther <agx@sigxcpu.org>
  */
 
+pub mod file_dialog;
 pub mod file_props;
 pub mod file_selector;
 pub mod init;
+pub mod open;
 
 mod bookmarks_box;
 mod bookmarks_item;
@@ -19,4 +21,5 @@ mod grid_item;
 mod path_bar;
 mod places_box;
 mod places_item;
+mod preview_pane;
 mod util;
@@ -0,0 +1,28 @@
+/*
+ * Copyright 2025 Phosh.mobi e.V.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ *
+ * Author: Guido Günther <agx@sigxcpu.org>
+ */
+
+//! Opening files with the user's preferred application.
+//!
+//! This module provides [`launch_uri`] which opens a URI in the default
+//! application without relying on external tools like `gio open`.
+
+use gtk::prelude::*;
+use gtk::{gdk, gio, glib};
+
+use crate::config::LOG_DOMAIN;
+
+/// Opens `uri` with the default application for its content type.
+///
+/// The launch context of the default display is used so the launched
+/// application gets e.g. startup notification.
+pub async fn launch_uri(uri: &str) -> Result<(), glib::Error> {
+    let ctx = gdk::Display::default().map(|d| d.app_launch_context());
+
+    glib::g_debug!(LOG_DOMAIN, "Launching {uri}");
+    gio::AppInfo::launch_default_for_uri_future(uri, ctx.as_ref()).await
+}
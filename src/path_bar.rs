@@ -10,11 +10,30 @@ use adw::prelude::*;
 use adw::subclass::prelude::*;
 use glib::subclass::Signal;
 use glib::Properties;
-use gtk::{gio, glib, CompositeTemplate};
-use std::cell::RefCell;
+use gtk::{gdk, gio, glib, CompositeTemplate};
+use std::cell::{Cell, RefCell};
 use std::sync::OnceLock;
 
-use crate::config::LOG_DOMAIN;
+use crate::{config::LOG_DOMAIN, file_selector::FileSelector, util};
+
+// Maximum number of folders offered for completion
+const MAX_COMPLETIONS: usize = 50;
+
+// The number of leading segments of the given widths to collapse into
+// the overflow menu so the others fit into `available` pixels. The
+// current folder and its parent always stay.
+fn n_hidden_to_fit(widths: &[i32], overflow_width: i32, available: i32) -> usize {
+    let mut needed: i32 = widths.iter().sum();
+    let mut n_hidden = 0;
+    while n_hidden + 2 < widths.len() && needed > available {
+        needed -= widths[n_hidden];
+        if n_hidden == 0 {
+            needed += overflow_width;
+        }
+        n_hidden += 1;
+    }
+    n_hidden
+}
 
 mod imp {
     use super::*;
@@ -29,9 +48,59 @@ mod imp {
         #[template_child]
         pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
 
+        #[template_child]
+        pub mode_stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub path_entry: TemplateChild<gtk::Entry>,
+
+        #[template_child]
+        pub edit_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub history_button: TemplateChild<gtk::MenuButton>,
+
+        #[template_child]
+        pub history_popover: TemplateChild<gtk::Popover>,
+
+        #[template_child]
+        pub history_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub completion_popover: TemplateChild<gtk::Popover>,
+
+        #[template_child]
+        pub completion_list: TemplateChild<gtk::ListBox>,
+
         // The current folder
         #[property(get, set = Self::set_folder)]
         folder: RefCell<Option<gio::File>>,
+
+        // Recently visited folders to offer for quick navigation
+        #[property(get, set = Self::set_history, nullable)]
+        pub(super) history: RefCell<Option<gio::ListModel>>,
+
+        // Whether to complete hidden folders too
+        #[property(get, set)]
+        show_hidden: Cell<bool>,
+
+        // Set while we update the entry ourselves
+        pub(super) updating_entry: Cell<bool>,
+        // Bumped on each completion request so stale results get dropped
+        pub(super) completion_serial: Cell<u32>,
+        // The folder part of the entry the completions are relative to
+        pub(super) completion_base: RefCell<String>,
+        pub(super) completions: RefCell<Vec<String>>,
+
+        // The folder's ancestors with their buttons and the buttons'
+        // natural width when last shown
+        segments: RefCell<Vec<(std::path::PathBuf, gtk::Button, i32)>>,
+        // The "…" button revealing the collapsed leading segments
+        overflow_button: RefCell<Option<gtk::MenuButton>>,
+        overflow_width: Cell<i32>,
+        // Number of leading segments that don't fit and are collapsed
+        n_hidden: Cell<usize>,
+        collapse_id: RefCell<Option<glib::SourceId>>,
     }
 
     #[glib::object_subclass]
@@ -51,12 +120,105 @@ mod imp {
     }
 
     impl PathBar {
+        // The "…" button revealing the collapsed leading segments
+        fn overflow_button(&self) -> gtk::MenuButton {
+            gtk::MenuButton::builder()
+                .label("…")
+                .tooltip_text(gettextrs::gettext("Show Parent Folders"))
+                .popover(&gtk::Popover::new())
+                .visible(false)
+                .build()
+        }
+
+        // Collapses the first `n_hidden` segments into the overflow button
+        fn set_n_hidden(&self, n_hidden: usize) {
+            self.n_hidden.set(n_hidden);
+
+            let Some(overflow_button) = self.overflow_button.borrow().clone() else {
+                return;
+            };
+            let popover = overflow_button.popover().unwrap();
+            let list = gtk::Box::new(gtk::Orientation::Vertical, 0);
+
+            for (i, (path, button, _)) in self.segments.borrow().iter().enumerate() {
+                button.set_visible(i >= n_hidden);
+                if i >= n_hidden {
+                    continue;
+                }
+
+                let item = gtk::Button::builder()
+                    .label(button.label().unwrap_or_default())
+                    .tooltip_text(path.to_string_lossy().to_string())
+                    .css_classes(["flat"])
+                    .build();
+                item.connect_clicked(glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    #[weak]
+                    popover,
+                    #[strong]
+                    path,
+                    move |_| {
+                        popover.popdown();
+                        this.obj().emit_path(&path);
+                    }
+                ));
+                list.append(&item);
+            }
+            popover.set_child(Some(&list));
+            overflow_button.set_visible(n_hidden > 0);
+        }
+
+        fn fitting_n_hidden(&self, available: i32) -> usize {
+            let mut segments = self.segments.borrow_mut();
+            for (_, button, width) in segments.iter_mut() {
+                // Hidden buttons measure as zero so keep what they had
+                if button.is_visible() {
+                    *width = button.measure(gtk::Orientation::Horizontal, -1).1;
+                }
+            }
+            if let Some(button) = self.overflow_button.borrow().as_ref() {
+                if button.is_visible() {
+                    self.overflow_width
+                        .set(button.measure(gtk::Orientation::Horizontal, -1).1);
+                }
+            }
+
+            let widths: Vec<i32> = segments.iter().map(|(_, _, width)| *width).collect();
+            n_hidden_to_fit(&widths, self.overflow_width.get(), available)
+        }
+
+        fn set_history(&self, history: Option<gio::ListModel>) {
+            self.history_list.bind_model(history.as_ref(), |object| {
+                let file = object.downcast_ref::<gio::File>().unwrap();
+                let label = gtk::Label::builder()
+                    .label(util::folder_to_name(file.clone()))
+                    .xalign(0.0)
+                    .ellipsize(gtk::pango::EllipsizeMode::Middle)
+                    .max_width_chars(30)
+                    .build();
+
+                let row = gtk::ListBoxRow::builder().child(&label).build();
+                let tooltip = file
+                    .path()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_else(|| file.uri().to_string());
+                row.set_tooltip_text(Some(&tooltip));
+                row.upcast()
+            });
+
+            *self.history.borrow_mut() = history;
+        }
+
         fn set_folder(&self, folder: Option<gio::File>) {
             let Some(folder) = folder else { return };
 
             while let Some(child) = self.path_box.first_child() {
                 self.path_box.remove(&child);
             }
+            self.segments.borrow_mut().clear();
+            self.overflow_button.take();
+            self.n_hidden.set(0);
 
             let Some(path) = folder.path() else {
                 self.path_box.set_visible(false);
@@ -64,38 +226,29 @@ mod imp {
             };
 
             self.path_box.set_visible(true);
-            for part in &path {
-                let button = gtk::Button::new();
-                button.set_label(part.to_str().unwrap());
+
+            // Leading segments get collapsed into it once we know how
+            // much space there is
+            let overflow_button = self.overflow_button();
+            self.path_box.append(&overflow_button);
+            self.overflow_button.replace(Some(overflow_button));
+
+            // The folder and all its ancestors
+            let mut pathbuf = std::path::PathBuf::new();
+            for part in path.iter() {
+                pathbuf.push(part);
+                let path = pathbuf.clone();
+
+                let button = gtk::Button::with_label(&part.to_string_lossy());
                 self.path_box.append(&button);
                 button.connect_clicked(glib::clone!(
                     #[weak(rename_to = this)]
                     self,
-                    move |clicked_button| {
-                        let mut child = this.path_box.first_child().unwrap();
-
-                        let mut pathbuf = std::path::PathBuf::new();
-                        loop {
-                            let button = child.downcast_ref::<gtk::Button>().unwrap();
-                            pathbuf.push(button.label().unwrap());
-
-                            if child == *clicked_button.upcast_ref::<gtk::Widget>() {
-                                break;
-                            }
-
-                            child = match child.next_sibling() {
-                                Some(child) => child,
-                                None => break,
-                            };
-                        }
-
-                        let file = gio::File::for_path(pathbuf);
-                        let uri = file.uri();
-                        glib::g_debug!(LOG_DOMAIN, "Selected path {uri:#?}");
-
-                        this.obj().emit_by_name::<()>("new-uri", &[&uri]);
-                    }
+                    #[strong]
+                    path,
+                    move |_| this.obj().emit_path(&path)
                 ));
+                self.segments.borrow_mut().push((path, button, 0));
             }
         }
     }
@@ -104,6 +257,14 @@ mod imp {
     impl ObjectImpl for PathBar {
         fn constructed(&self) {
             self.parent_constructed();
+
+            self.completion_popover.set_parent(&*self.path_entry);
+        }
+
+        fn dispose(&self) {
+            if self.completion_popover.parent().is_some() {
+                self.completion_popover.unparent();
+            }
         }
 
         fn signals() -> &'static [Signal] {
@@ -116,7 +277,37 @@ mod imp {
         }
     }
 
-    impl WidgetImpl for PathBar {}
+    impl WidgetImpl for PathBar {
+        fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+            self.parent_size_allocate(width, height, baseline);
+
+            // Nothing to fit while the location entry is shown
+            if !self.scrolled_window.is_mapped() || self.collapse_id.borrow().is_some() {
+                return;
+            }
+
+            let n_hidden = self.fitting_n_hidden(self.scrolled_window.width());
+            if n_hidden == self.n_hidden.get() {
+                return;
+            }
+
+            // Don't change the widget tree while allocating it
+            let source_id = glib::idle_add_local_once(glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                move || {
+                    this.collapse_id.take();
+                    if !this.scrolled_window.is_mapped() {
+                        return;
+                    }
+                    let n_hidden = this.fitting_n_hidden(this.scrolled_window.width());
+                    this.set_n_hidden(n_hidden);
+                }
+            ));
+            self.collapse_id.replace(Some(source_id));
+        }
+    }
+
     impl BinImpl for PathBar {}
 }
 
@@ -137,4 +328,302 @@ impl PathBar {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn emit_path(&self, path: &std::path::Path) {
+        let uri = gio::File::for_path(path).uri();
+        glib::g_debug!(LOG_DOMAIN, "Selected path {uri:#?}");
+
+        self.emit_by_name::<()>("new-uri", &[&uri]);
+    }
+
+    /// Swaps the breadcrumbs for an entry to type or paste a location
+    pub fn start_editing(&self) {
+        self.imp().edit_button.set_active(true);
+    }
+
+    pub fn stop_editing(&self) {
+        self.imp().edit_button.set_active(false);
+    }
+
+    #[template_callback]
+    fn on_edit_toggled(&self) {
+        let imp = self.imp();
+
+        if !imp.edit_button.is_active() {
+            self.hide_completions();
+            imp.mode_stack.set_visible_child_name("crumbs");
+            return;
+        }
+
+        let text = self
+            .folder()
+            .map(|folder| match folder.path() {
+                Some(path) => path.to_string_lossy().to_string(),
+                None => folder.uri().to_string(),
+            })
+            .unwrap_or_default();
+        imp.updating_entry.set(true);
+        imp.path_entry.set_text(&text);
+        imp.updating_entry.set(false);
+        imp.mode_stack.set_visible_child_name("entry");
+        imp.path_entry.grab_focus();
+        imp.path_entry.set_position(-1);
+    }
+
+    #[template_callback]
+    fn on_path_entry_key_pressed(&self, keyval: gdk::Key) -> bool {
+        match keyval {
+            gdk::Key::Escape => {
+                self.stop_editing();
+                true
+            }
+            gdk::Key::Tab => self.complete_common_prefix(),
+            _ => false,
+        }
+    }
+
+    #[template_callback]
+    fn on_path_entry_changed(&self) {
+        self.imp().path_entry.remove_css_class("error");
+        self.update_completions();
+    }
+
+    #[template_callback]
+    fn on_completion_row_activated(&self, row: &gtk::ListBoxRow) {
+        let imp = self.imp();
+        let Some(name) = imp.completions.borrow().get(row.index() as usize).cloned() else {
+            return;
+        };
+
+        let text = format!("{}{name}/", imp.completion_base.borrow());
+        self.set_entry_text(&text);
+    }
+
+    fn set_entry_text(&self, text: &str) {
+        let imp = self.imp();
+
+        imp.path_entry.set_text(text);
+        imp.path_entry.grab_focus();
+        imp.path_entry.set_position(-1);
+    }
+
+    fn hide_completions(&self) {
+        let imp = self.imp();
+
+        imp.completion_serial
+            .set(imp.completion_serial.get().wrapping_add(1));
+        imp.completions.borrow_mut().clear();
+        imp.completion_popover.popdown();
+    }
+
+    // Complete the folder name typed so far as far as it's unambiguous
+    fn complete_common_prefix(&self) -> bool {
+        let imp = self.imp();
+        let completions = imp.completions.borrow().clone();
+        let Some(first) = completions.first() else {
+            return false;
+        };
+
+        let mut common = first.clone();
+        for name in completions.iter().skip(1) {
+            let len = common
+                .chars()
+                .zip(name.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.len_utf8())
+                .sum();
+            common.truncate(len);
+        }
+
+        let mut text = format!("{}{common}", imp.completion_base.borrow());
+        if completions.len() == 1 {
+            text.push('/');
+        }
+        self.set_entry_text(&text);
+        true
+    }
+
+    // Subfolders of `dir` starting with `prefix`
+    async fn matching_folders(dir: gio::File, prefix: &str, show_hidden: bool) -> Vec<String> {
+        let Ok(enumerator) = dir
+            .enumerate_children_future(
+                "standard::name,standard::type,standard::is-hidden",
+                gio::FileQueryInfoFlags::NONE,
+                glib::Priority::DEFAULT,
+            )
+            .await
+        else {
+            return Vec::new();
+        };
+
+        // Typing a leading dot means the user is after a hidden folder
+        let show_hidden = show_hidden || prefix.starts_with('.');
+        let mut matches = Vec::new();
+        while let Ok(infos) = enumerator
+            .next_files_future(64, glib::Priority::DEFAULT)
+            .await
+        {
+            if infos.is_empty() {
+                break;
+            }
+
+            for info in infos {
+                if info.file_type() != gio::FileType::Directory
+                    || (info.is_hidden() && !show_hidden)
+                {
+                    continue;
+                }
+
+                let name = info.name().to_string_lossy().to_string();
+                if name.starts_with(prefix) {
+                    matches.push(name);
+                }
+            }
+        }
+
+        matches.sort();
+        matches.truncate(MAX_COMPLETIONS);
+        matches
+    }
+
+    fn update_completions(&self) {
+        let imp = self.imp();
+
+        self.hide_completions();
+        if imp.updating_entry.get() || !imp.edit_button.is_active() {
+            return;
+        }
+
+        let text = imp.path_entry.text().to_string();
+        let Some(pos) = text.rfind('/') else {
+            return;
+        };
+        let (base, prefix) = text.split_at(pos + 1);
+        let (base, prefix) = (base.to_string(), prefix.to_string());
+
+        let dir = self.location_to_file(&base);
+        let serial = imp.completion_serial.get();
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let matches = Self::matching_folders(dir, &prefix, this.show_hidden()).await;
+                let imp = this.imp();
+
+                // The entry changed in the meantime
+                if imp.completion_serial.get() != serial {
+                    return;
+                }
+
+                imp.completion_list.remove_all();
+                for name in &matches {
+                    let label = gtk::Label::builder().label(name).xalign(0.0).build();
+                    imp.completion_list.append(&label);
+                }
+
+                let complete = matches.len() == 1 && matches[0] == prefix;
+                *imp.completion_base.borrow_mut() = base;
+                *imp.completions.borrow_mut() = matches;
+                if !imp.completions.borrow().is_empty() && !complete {
+                    imp.completion_popover.popup();
+                }
+            }
+        ));
+    }
+
+    // Turn what the user typed into a file, relative paths are relative
+    // to the current folder
+    fn location_to_file(&self, text: &str) -> gio::File {
+        let text = match text.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                format!("{}{rest}", glib::home_dir().to_string_lossy())
+            }
+            _ => text.to_string(),
+        };
+
+        let cwd = self
+            .folder()
+            .and_then(|folder| folder.path())
+            .unwrap_or_else(glib::home_dir);
+        gio::File::for_commandline_arg_and_cwd(text, cwd)
+    }
+
+    #[template_callback]
+    fn on_path_entry_activate(&self) {
+        let text = self.imp().path_entry.text();
+        if text.trim().is_empty() {
+            return;
+        }
+
+        let file = self.location_to_file(text.trim());
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let res = file
+                    .query_info_future(
+                        gio::FILE_ATTRIBUTE_STANDARD_TYPE,
+                        gio::FileQueryInfoFlags::NONE,
+                        glib::Priority::DEFAULT,
+                    )
+                    .await;
+
+                let error = match res {
+                    Ok(info) if info.file_type() == gio::FileType::Directory => {
+                        this.stop_editing();
+                        let uri = file.uri();
+                        glib::g_debug!(LOG_DOMAIN, "Entered location {uri:#?}");
+                        this.emit_by_name::<()>("new-uri", &[&uri]);
+                        return;
+                    }
+                    Ok(_) => gettextrs::gettext("“{}” is not a folder"),
+                    Err(_) => gettextrs::gettext("“{}” does not exist"),
+                };
+
+                this.imp().path_entry.add_css_class("error");
+                let msg = error.replacen("{}", &text, 1);
+                if let Some(file_selector) = this.root().and_downcast::<FileSelector>() {
+                    file_selector.show_toast(adw::Toast::new(&msg));
+                }
+            }
+        ));
+    }
+
+    #[template_callback]
+    fn on_history_row_activated(&self, row: &gtk::ListBoxRow) {
+        let imp = self.imp();
+        let Some(history) = imp.history.borrow().clone() else {
+            return;
+        };
+        let Some(file) = history.item(row.index() as u32).and_downcast::<gio::File>() else {
+            return;
+        };
+
+        imp.history_popover.popdown();
+
+        let uri = file.uri();
+        glib::g_debug!(LOG_DOMAIN, "Selected recent folder {uri:#?}");
+        self.emit_by_name::<()>("new-uri", &[&uri]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_n_hidden_to_fit() {
+        // Everything fits
+        assert_eq!(n_hidden_to_fit(&[50, 50, 50], 30, 200), 0);
+
+        // Collapse just enough leading segments
+        assert_eq!(n_hidden_to_fit(&[50, 50, 50, 50], 30, 170), 2);
+
+        // A deep path in a narrow bar keeps the folder and its parent
+        let widths = [40, 60, 80, 60, 70, 90, 120];
+        assert_eq!(n_hidden_to_fit(&widths, 30, 100), widths.len() - 2);
+
+        // Nothing to collapse into the overflow menu
+        assert_eq!(n_hidden_to_fit(&[300, 300], 30, 100), 0);
+    }
 }
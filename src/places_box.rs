@@ -12,9 +12,15 @@ use glib::subclass::Signal;
 use glib::translate::*;
 use glib::Object;
 use gtk::{gio, glib, CompositeTemplate};
+use std::cell::{Cell, RefCell};
 use std::sync::OnceLock;
 
-use crate::{config::LOG_DOMAIN, places_item::PlacesItem, util};
+use crate::{
+    bookmarks_box::{gtk_bookmarks_file, load_bookmarks_file, parse_bookmark, update_bookmarks},
+    config::LOG_DOMAIN,
+    places_item::PlacesItem,
+    util,
+};
 
 mod imp {
     use super::*;
@@ -24,6 +30,16 @@ mod imp {
     pub struct PlacesBox {
         #[template_child]
         pub flow_box: TemplateChild<gtk::FlowBox>,
+
+        pub bookmark_items: RefCell<Vec<PlacesItem>>,
+        pub bookmarks_monitor: RefCell<Option<gio::FileMonitor>>,
+
+        pub trash_monitor: RefCell<Option<gio::FileMonitor>>,
+
+        pub volume_monitor: RefCell<Option<gio::VolumeMonitor>>,
+        pub mount_items: RefCell<Vec<PlacesItem>>,
+        // Mounts are inserted after the fixed places
+        pub mounts_position: Cell<i32>,
     }
 
     #[glib::object_subclass]
@@ -87,8 +103,19 @@ mod imp {
                 .property("uri", "trash:///")
                 .build();
             self.flow_box.append(&item);
+            self.obj().setup_trash_badge(&item);
+
+            let position = item
+                .parent()
+                .and_downcast::<gtk::FlowBoxChild>()
+                .map(|child| child.index() + 1)
+                .unwrap_or(-1);
+            self.mounts_position.set(position);
+            self.obj().setup_mounts();
 
-            // TODO: mounts, bookmarks, other locations
+            self.obj().setup_bookmarks();
+
+            // TODO: other locations
         }
 
         fn signals() -> &'static [Signal] {
@@ -133,4 +160,202 @@ impl PlacesBox {
         glib::g_debug!(LOG_DOMAIN, "Should open {uri:#?}");
         self.emit_by_name::<()>("new-uri", &[&uri]);
     }
+
+    fn setup_mounts(&self) {
+        let monitor = gio::VolumeMonitor::get();
+
+        monitor.connect_mount_added(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_, _| this.update_mounts()
+        ));
+        monitor.connect_mount_removed(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_, _| this.update_mounts()
+        ));
+        monitor.connect_mount_changed(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            move |_, _| this.update_mounts()
+        ));
+
+        *self.imp().volume_monitor.borrow_mut() = Some(monitor);
+        self.update_mounts();
+    }
+
+    fn update_mounts(&self) {
+        let imp = self.imp();
+        let Some(monitor) = imp.volume_monitor.borrow().clone() else {
+            return;
+        };
+
+        for item in imp.mount_items.borrow_mut().drain(..) {
+            imp.flow_box.remove(&item);
+        }
+
+        let mut position = imp.mounts_position.get();
+        for mount in monitor.mounts() {
+            // Mounts hidden by e.g. a bind mount elsewhere
+            if mount.is_shadowed() {
+                continue;
+            }
+
+            let root = mount.root();
+            let icon_name = mount
+                .symbolic_icon()
+                .downcast::<gio::ThemedIcon>()
+                .ok()
+                .and_then(|icon| icon.names().first().map(|name| name.to_string()))
+                .unwrap_or_else(|| "drive-removable-media-symbolic".to_string());
+
+            glib::g_debug!(LOG_DOMAIN, "Adding mount {:#?}", root.uri());
+            let item = Object::builder::<PlacesItem>()
+                .property("place", mount.name())
+                .property("icon-name", icon_name)
+                .property("uri", root.uri())
+                .property("mount", &mount)
+                .build();
+
+            imp.flow_box.insert(&item, position);
+            if position >= 0 {
+                position += 1;
+            }
+            imp.mount_items.borrow_mut().push(item);
+        }
+    }
+
+    fn setup_trash_badge(&self, item: &PlacesItem) {
+        let trash = gio::File::for_uri("trash:///");
+
+        match trash.monitor_directory(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>) {
+            Ok(monitor) => {
+                monitor.connect_changed(glib::clone!(
+                    #[weak]
+                    item,
+                    move |_, _, _, _| {
+                        Self::update_trash_badge(&item);
+                    }
+                ));
+                *self.imp().trash_monitor.borrow_mut() = Some(monitor);
+            }
+            Err(error) => glib::g_debug!(LOG_DOMAIN, "Can't monitor trash: {error}"),
+        }
+
+        Self::update_trash_badge(item);
+    }
+
+    fn update_trash_badge(item: &PlacesItem) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak]
+            item,
+            async move {
+                let trash = gio::File::for_uri("trash:///");
+                let count = match trash
+                    .query_info_future(
+                        "trash::item-count",
+                        gio::FileQueryInfoFlags::NONE,
+                        glib::Priority::LOW,
+                    )
+                    .await
+                {
+                    Ok(info) => info.attribute_uint32("trash::item-count"),
+                    Err(error) => {
+                        glib::g_debug!(LOG_DOMAIN, "Can't get trash item count: {error}");
+                        0
+                    }
+                };
+
+                let badge = if count > 0 {
+                    count.to_string()
+                } else {
+                    String::new()
+                };
+                item.set_badge(badge);
+            }
+        ));
+    }
+
+    fn setup_bookmarks(&self) {
+        let file = gtk_bookmarks_file();
+
+        // Pick up changes by other apps too
+        match file.monitor_file(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>) {
+            Ok(monitor) => {
+                monitor.connect_changed(glib::clone!(
+                    #[weak(rename_to = this)]
+                    self,
+                    move |_, _, _, event| {
+                        if event == gio::FileMonitorEvent::ChangesDoneHint
+                            || event == gio::FileMonitorEvent::Deleted
+                        {
+                            this.reload_bookmarks();
+                        }
+                    }
+                ));
+                *self.imp().bookmarks_monitor.borrow_mut() = Some(monitor);
+            }
+            Err(error) => glib::g_warning!(LOG_DOMAIN, "Can't monitor bookmarks: {error}"),
+        }
+
+        self.reload_bookmarks();
+    }
+
+    fn reload_bookmarks(&self) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let contents = load_bookmarks_file(&gtk_bookmarks_file()).await;
+                this.show_bookmarks(&contents);
+            }
+        ));
+    }
+
+    fn show_bookmarks(&self, contents: &str) {
+        let imp = self.imp();
+
+        for item in imp.bookmark_items.borrow_mut().drain(..) {
+            imp.flow_box.remove(&item);
+        }
+
+        for (uri, label) in contents.lines().filter_map(parse_bookmark) {
+            let folder = gio::File::for_uri(uri);
+            let place = label
+                .map(str::to_string)
+                .unwrap_or_else(|| util::folder_to_name(folder.clone()));
+
+            let item = Object::builder::<PlacesItem>()
+                .property("place", place)
+                .property("icon-name", util::folder_to_icon_name(folder))
+                .property("uri", uri)
+                .property("removable", true)
+                .build();
+            imp.flow_box.append(&item);
+            imp.bookmark_items.borrow_mut().push(item);
+        }
+    }
+
+    /// Removes `uri` from the user's GTK bookmarks
+    pub fn remove_bookmark(&self, uri: &str) {
+        let uri = uri.to_string();
+        glib::g_debug!(LOG_DOMAIN, "Removing bookmark {uri:#?}");
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let res = update_bookmarks(move |lines| {
+                    lines.retain(|line| !parse_bookmark(line).is_some_and(|(u, _)| u == uri));
+                })
+                .await;
+
+                if let Err(error) = res {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to save bookmarks: {error}");
+                    return;
+                }
+                this.reload_bookmarks();
+            }
+        ));
+    }
 }
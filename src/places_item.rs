@@ -9,8 +9,10 @@
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use glib::Properties;
-use gtk::{glib, CompositeTemplate};
-use std::cell::RefCell;
+use gtk::{gio, glib, CompositeTemplate};
+use std::cell::{Cell, RefCell};
+
+use crate::{config::LOG_DOMAIN, file_selector::FileSelector, places_box::PlacesBox};
 
 mod imp {
     use super::*;
@@ -25,6 +27,15 @@ mod imp {
         #[template_child]
         pub label: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        pub badge_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub eject_stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub context_menu: TemplateChild<gtk::PopoverMenu>,
+
         #[property(get, set)]
         place: RefCell<String>,
 
@@ -33,6 +44,18 @@ mod imp {
 
         #[property(get, set)]
         uri: RefCell<String>,
+
+        // Whether this is a user bookmark that can be removed
+        #[property(get, set)]
+        removable: Cell<bool>,
+
+        // Short text shown on top of the icon, e.g. a number of items
+        #[property(get, set = Self::set_badge, explicit_notify)]
+        badge: RefCell<String>,
+
+        // The mount if this item represents a mounted volume
+        #[property(get, set = Self::set_mount, nullable, explicit_notify)]
+        mount: RefCell<Option<gio::Mount>>,
     }
 
     #[glib::object_subclass]
@@ -44,6 +67,14 @@ mod imp {
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
             klass.bind_template_instance_callbacks();
+
+            klass.install_action("places-item.remove", None, move |item, _, _| {
+                item.remove();
+            });
+
+            klass.install_action("places-item.eject", None, move |item, _, _| {
+                item.eject();
+            });
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -51,13 +82,44 @@ mod imp {
         }
     }
 
-    impl PlacesItem {}
+    impl PlacesItem {
+        fn set_badge(&self, badge: String) {
+            if *self.badge.borrow() == badge {
+                return;
+            }
+
+            self.badge_label.set_visible(!badge.is_empty());
+            *self.badge.borrow_mut() = badge;
+            self.obj().notify_badge();
+        }
+
+        fn set_mount(&self, mount: Option<gio::Mount>) {
+            if *self.mount.borrow() == mount {
+                return;
+            }
+
+            let ejectable = mount
+                .as_ref()
+                .is_some_and(|mount| mount.can_eject() || mount.can_unmount());
+            self.eject_stack.set_visible(ejectable);
+            self.eject_stack.set_visible_child_name("button");
+
+            *self.mount.borrow_mut() = mount;
+            self.obj().notify_mount();
+        }
+    }
 
     #[glib::derived_properties]
     impl ObjectImpl for PlacesItem {
         fn constructed(&self) {
             self.parent_constructed();
         }
+
+        fn dispose(&self) {
+            if self.context_menu.parent().is_some() {
+                self.context_menu.unparent();
+            }
+        }
     }
 
     impl WidgetImpl for PlacesItem {}
@@ -81,4 +143,91 @@ impl PlacesItem {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn remove(&self) {
+        let Some(places_box) = self
+            .ancestor(PlacesBox::static_type())
+            .and_downcast::<PlacesBox>()
+        else {
+            return;
+        };
+
+        places_box.remove_bookmark(&self.uri());
+    }
+
+    fn eject(&self) {
+        let Some(mount) = self.mount() else {
+            return;
+        };
+
+        let imp = self.imp();
+        imp.eject_stack.set_visible_child_name("spinner");
+        self.action_set_enabled("places-item.eject", false);
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let window = this.root().and_downcast::<gtk::Window>();
+                let operation = gtk::MountOperation::new(window.as_ref());
+                let flags = gio::MountUnmountFlags::NONE;
+
+                let res = if mount.can_eject() {
+                    mount
+                        .eject_with_operation_future(flags, Some(&operation))
+                        .await
+                } else {
+                    mount
+                        .unmount_with_operation_future(flags, Some(&operation))
+                        .await
+                };
+
+                // On success the item goes away with the mount
+                this.imp().eject_stack.set_visible_child_name("button");
+                this.action_set_enabled("places-item.eject", true);
+
+                let Err(err) = res else {
+                    return;
+                };
+
+                // The user got informed already, e.g. cancelled a dialog
+                if err.matches(gio::IOErrorEnum::FailedHandled) {
+                    return;
+                }
+
+                let name = mount.name();
+                glib::g_warning!(LOG_DOMAIN, "Failed to eject {name}: {err}");
+                let msg = if err.matches(gio::IOErrorEnum::Busy) {
+                    gettextrs::gettext("“{}” is busy")
+                } else {
+                    gettextrs::gettext("Failed to eject “{}”")
+                }
+                .replacen("{}", &name, 1);
+
+                if let Some(file_selector) = this.root().and_downcast::<FileSelector>() {
+                    file_selector.show_toast(adw::Toast::new(&msg));
+                }
+            }
+        ));
+    }
+
+    fn show_context_menu(&self, x: f64, y: f64) {
+        if !self.removable() {
+            return;
+        }
+
+        let popover = &self.imp().context_menu;
+        popover.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+        popover.popup();
+    }
+
+    #[template_callback]
+    fn on_long_press_pressed(&self, x: f64, y: f64) {
+        self.show_context_menu(x, y);
+    }
+
+    #[template_callback]
+    fn on_right_click_pressed(&self, _n_press: i32, x: f64, y: f64) {
+        self.show_context_menu(x, y);
+    }
 }
@@ -0,0 +1,202 @@
+/*
+ * Copyright 2025 Phosh.mobi e.V.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ *
+ * Author: Guido Günther <agx@sigxcpu.org>
+ */
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::Properties;
+use gtk::{gdk, gdk_pixbuf, gio, glib, CompositeTemplate};
+use std::cell::{Cell, RefCell};
+
+use crate::{config::LOG_DOMAIN, util};
+
+// Only show the beginning of text files
+const PREVIEW_TEXT_MAX_BYTES: usize = 8 * 1024;
+// Scale images down so large photos don't need to be decoded at full size
+const PREVIEW_IMAGE_MAX_SIZE: i32 = 1024;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, Properties)]
+    #[template(resource = "/mobi/phosh/FileSelector/preview-pane.ui")]
+    #[properties(wrapper_type = super::PreviewPane)]
+    pub struct PreviewPane {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub icon: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub picture: TemplateChild<gtk::Picture>,
+
+        #[template_child]
+        pub text_view: TemplateChild<gtk::TextView>,
+
+        // The file info of the file to preview
+        #[property(get, set = Self::set_fileinfo, nullable, explicit_notify)]
+        pub fileinfo: RefCell<Option<gio::FileInfo>>,
+
+        // Guards against results of outdated loads
+        pub serial: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PreviewPane {
+        const NAME: &'static str = "PfsPreviewPane";
+        type Type = super::PreviewPane;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl PreviewPane {
+        fn set_fileinfo(&self, fileinfo: Option<gio::FileInfo>) {
+            if *self.fileinfo.borrow() == fileinfo {
+                return;
+            }
+
+            *self.fileinfo.borrow_mut() = fileinfo;
+            self.obj().update_preview();
+            self.obj().notify_fileinfo();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for PreviewPane {}
+
+    impl WidgetImpl for PreviewPane {}
+    impl BinImpl for PreviewPane {}
+}
+
+glib::wrapper! {
+    pub struct PreviewPane(ObjectSubclass<imp::PreviewPane>)
+        @extends adw::Bin, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl Default for PreviewPane {
+    fn default() -> Self {
+        glib::Object::new::<Self>()
+    }
+}
+
+impl PreviewPane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update_preview(&self) {
+        let imp = self.imp();
+        let serial = imp.serial.get().wrapping_add(1);
+        imp.serial.set(serial);
+
+        let Some(info) = self.fileinfo() else {
+            imp.stack.set_visible_child_name("empty");
+            return;
+        };
+
+        let Some(file) = info
+            .attribute_object("standard::file")
+            .and_downcast::<gio::File>()
+        else {
+            imp.stack.set_visible_child_name("empty");
+            return;
+        };
+
+        // Show what we have right away, the real preview loads async
+        if let Some(path) = util::cached_thumbnail_path(&info) {
+            imp.picture.set_filename(Some(path.as_str()));
+            imp.stack.set_visible_child_name("image");
+        } else if let Some(icon) = info.icon() {
+            imp.icon.set_from_gicon(&icon);
+            imp.stack.set_visible_child_name("icon");
+        } else {
+            imp.stack.set_visible_child_name("empty");
+        }
+
+        let content_type = info.content_type().unwrap_or_default();
+        if gio::content_type_is_mime_type(&content_type, "image/*") {
+            self.load_image(file, serial);
+        } else if gio::content_type_is_a(&content_type, "text/plain") {
+            self.load_text(file, serial);
+        }
+    }
+
+    fn load_image(&self, file: gio::File, serial: u32) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = this)]
+            self,
+            async move {
+                let pixbuf = match file.read_future(glib::Priority::DEFAULT).await {
+                    Ok(stream) => {
+                        gdk_pixbuf::Pixbuf::from_stream_at_scale_future(
+                            &stream,
+                            PREVIEW_IMAGE_MAX_SIZE,
+                            PREVIEW_IMAGE_MAX_SIZE,
+                            true,
+                        )
+                        .await
+                    }
+                    Err(err) => Err(err),
+                };
+
+                let imp = this.imp();
+                if imp.serial.get() != serial {
+                    return;
+                }
+
+                match pixbuf {
+                    Ok(pixbuf) => {
+                        let texture = gdk::Texture::for_pixbuf(&pixbuf);
+                        imp.picture.set_paintable(Some(&texture));
+                        imp.stack.set_visible_child_name("image");
+                    }
+                    Err(err) => glib::g_debug!(LOG_DOMAIN, "Can't preview {}: {err}", file.uri()),
+                }
+            }
+        ));
+    }
+
+    fn load_text(&self, file: gio::File, serial: u32) {
+        file.load_partial_contents_async(
+            None::<&gio::Cancellable>,
+            |contents| contents.len() < PREVIEW_TEXT_MAX_BYTES,
+            glib::clone!(
+                #[weak(rename_to = this)]
+                self,
+                #[strong]
+                file,
+                move |res| {
+                    let imp = this.imp();
+                    if imp.serial.get() != serial {
+                        return;
+                    }
+
+                    match res {
+                        Ok((contents, _)) => {
+                            let len = contents.len().min(PREVIEW_TEXT_MAX_BYTES);
+                            let text = String::from_utf8_lossy(&contents[..len]);
+                            imp.text_view.buffer().set_text(&text);
+                            imp.stack.set_visible_child_name("text");
+                        }
+                        Err(err) => {
+                            glib::g_debug!(LOG_DOMAIN, "Can't preview {}: {err}", file.uri())
+                        }
+                    }
+                }
+            ),
+        );
+    }
+}
@@ -81,13 +81,188 @@ pub fn folder_to_icon_name(file: gio::File) -> &'static str {
     "folder-symbolic"
 }
 
+// The path of an up to date thumbnail GIO already knows about (if any)
+pub fn cached_thumbnail_path(info: &gio::FileInfo) -> Option<glib::GString> {
+    if !info.boolean(gio::FILE_ATTRIBUTE_THUMBNAIL_IS_VALID) {
+        return None;
+    }
+
+    info.attribute_byte_string(gio::FILE_ATTRIBUTE_THUMBNAIL_PATH)
+}
+
+// Whether GIO considers the file hidden (dot files, entries listed in
+// `.hidden`). Falls back to the dot prefix when the attribute wasn't
+// queried or the backend doesn't provide it.
+pub fn is_hidden(info: &gio::FileInfo) -> bool {
+    if info.has_attribute(gio::FILE_ATTRIBUTE_STANDARD_IS_HIDDEN) {
+        return info.is_hidden();
+    }
+
+    info.display_name().starts_with('.')
+}
+
+// Whether the file is a backup file like the `file~` left behind by editors
+pub fn is_backup(info: &gio::FileInfo) -> bool {
+    if info.has_attribute(gio::FILE_ATTRIBUTE_STANDARD_IS_BACKUP) {
+        return info.is_backup();
+    }
+
+    info.display_name().ends_with('~')
+}
+
+// Media duration in seconds as e.g. stored by the thumbnailer
+pub const MEDIA_DURATION_ATTRIBUTE: &str = "metadata::media-duration";
+
+// Format a media duration given in seconds as `H:MM:SS` or `M:SS`
+pub fn format_duration(secs: u64) -> String {
+    let (hours, mins, secs) = (secs / 3600, (secs / 60) % 60, secs % 60);
+
+    if hours > 0 {
+        format!("{hours}:{mins:02}:{secs:02}")
+    } else {
+        format!("{mins}:{secs:02}")
+    }
+}
+
+// Format a timestamp relative to now ("3 hours ago") when recent and
+// as a localized absolute date otherwise
+pub fn format_date_time(date_time: &glib::DateTime) -> Option<String> {
+    let date_time = date_time.to_local().ok()?;
+    let now = glib::DateTime::now_local().ok()?;
+    let secs = now.difference(&date_time).as_seconds();
+
+    // Timestamps in the future or older than a week get an absolute date
+    if !(0..7 * 24 * 3600).contains(&secs) {
+        return date_time.format("%x %X").ok().map(|s| s.to_string());
+    }
+
+    let (n, msg) = if secs < 60 {
+        return Some(gettextrs::gettext("Just now"));
+    } else if secs < 3600 {
+        let n = secs / 60;
+        (
+            n,
+            gettextrs::ngettext("{} minute ago", "{} minutes ago", n as u32),
+        )
+    } else if secs < 24 * 3600 {
+        let n = secs / 3600;
+        (
+            n,
+            gettextrs::ngettext("{} hour ago", "{} hours ago", n as u32),
+        )
+    } else {
+        let n = secs / (24 * 3600);
+        (
+            n,
+            gettextrs::ngettext("{} day ago", "{} days ago", n as u32),
+        )
+    };
+
+    Some(msg.replacen("{}", &n.to_string(), 1))
+}
+
+// Format a timestamp relative to now ("3 hours ago", "Yesterday") when
+// recent and as a short localized date otherwise
+pub fn format_relative_date_time(date_time: &glib::DateTime) -> Option<String> {
+    let date_time = date_time.to_local().ok()?;
+    let now = glib::DateTime::now_local().ok()?;
+    let secs = now.difference(&date_time).as_seconds();
+
+    // Compare calendar days so that e.g. 23:00 yesterday is "Yesterday"
+    let today =
+        glib::DateTime::from_local(now.year(), now.month(), now.day_of_month(), 0, 0, 0.0).ok()?;
+    let day = glib::DateTime::from_local(
+        date_time.year(),
+        date_time.month(),
+        date_time.day_of_month(),
+        0,
+        0,
+        0.0,
+    )
+    .ok()?;
+    let days = today.difference(&day).as_days();
+
+    let msg = if secs < 0 || days >= 7 {
+        // In the future or older than a week
+        date_time.format("%x").ok()?.to_string()
+    } else if secs < 60 {
+        gettextrs::gettext("Just now")
+    } else if secs < 3600 {
+        let n = secs / 60;
+        gettextrs::ngettext("{} minute ago", "{} minutes ago", n as u32).replacen(
+            "{}",
+            &n.to_string(),
+            1,
+        )
+    } else if days == 0 {
+        let n = secs / 3600;
+        gettextrs::ngettext("{} hour ago", "{} hours ago", n as u32).replacen(
+            "{}",
+            &n.to_string(),
+            1,
+        )
+    } else if days == 1 {
+        gettextrs::gettext("Yesterday")
+    } else {
+        gettextrs::ngettext("{} day ago", "{} days ago", days as u32).replacen(
+            "{}",
+            &days.to_string(),
+            1,
+        )
+    };
+
+    Some(msg)
+}
+
+// Format the permission bits of a unix mode like `ls -l`, e.g. `rwxr-xr-x`
+pub fn format_unix_mode(mode: u32) -> String {
+    let mut perms = String::with_capacity(9);
+
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        perms.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        perms.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        perms.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    perms
+}
+
 // Check if folder has a valid path (e.g. isn't recent:/// or trash:///
 pub fn is_valid_folder(folder: Option<&gio::File>) -> bool {
-    if folder.is_none() {
-        return false;
-    }
+    folder.is_some_and(|folder| folder.path().is_some() && !is_in_trash(folder))
+}
 
-    folder.as_ref().unwrap().path().is_some()
+// The URI of the file `info` refers to. Virtual locations like
+// recent:/// point to the real file via `standard::target-uri`
+pub fn target_uri(info: &gio::FileInfo, file: &gio::File) -> String {
+    info.attribute_string("standard::target-uri")
+        .map(|uri| uri.to_string())
+        .unwrap_or_else(|| file.uri().to_string())
+}
+
+// Check if file is the trash or a file within the trash
+pub fn is_in_trash(file: &gio::File) -> bool {
+    file.has_uri_scheme("trash")
+}
+
+thread_local! {
+    static SETTINGS: Option<gio::Settings> =
+        is_schema_installed().then(|| gio::Settings::new("mobi.phosh.FileSelector"));
+}
+
+// Format a size in bytes honoring the user's preferred units
+pub fn format_size(size: u64) -> glib::GString {
+    let iec = SETTINGS.with(|settings| {
+        settings
+            .as_ref()
+            .is_some_and(|settings| settings.boolean("iec-size-units"))
+    });
+
+    if iec {
+        glib::format_size_full(size, glib::FormatSizeFlags::IEC_UNITS)
+    } else {
+        glib::format_size(size)
+    }
 }
 
 pub fn is_schema_installed() -> bool {
@@ -1,7 +1,10 @@
-use gtk::gio;
 use gtk::prelude::*;
+use gtk::{gio, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
 
-use pfs::file_selector::FileSelectorBuilder;
+use pfs::file_dialog::FileDialog;
+use pfs::file_selector::{ActivateAction, FileSelector, FileSelectorBuilder, FileSelectorMode};
 
 #[cfg(test)]
 mod tests {
@@ -9,17 +12,254 @@ mod tests {
 
     #[test]
     fn test_new_file_selector() {
-        assert_eq!(gtk::init().is_ok(), true);
-        pfs::init::init();
-
-        let file_selector = FileSelectorBuilder::new()
-            .accept_label("Done")
-            .title("Select a File")
-            .current_folder(gio::File::for_path("/tmp"))
-            .build();
-
-        assert_eq!(file_selector.selected_choices().is_none(), true);
-        assert_eq!(file_selector.current_folder().is_some(), true);
-        assert_eq!(file_selector.current_folder().unwrap().uri(), "file:///tmp");
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new()
+                .accept_label("Done")
+                .title("Select a File")
+                .current_folder(gio::File::for_path("/tmp"))
+                .build();
+
+            assert_eq!(file_selector.selected_choices().is_none(), true);
+            assert_eq!(file_selector.current_folder().is_some(), true);
+            assert_eq!(file_selector.current_folder().unwrap().uri(), "file:///tmp");
+            // Nothing got loaded yet as the main loop didn't run
+            assert_eq!(file_selector.model().n_items(), 0);
+        });
+    }
+
+    #[test]
+    fn test_add_filter_for_extensions() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new().build();
+
+            file_selector.add_filter_for_extensions("Images", &["jpg", "*.png"]);
+            file_selector.add_filter_for_extensions("Text", &[".txt"]);
+
+            let filters = file_selector.filters().unwrap();
+            assert_eq!(filters.n_items(), 2);
+            assert_eq!(file_selector.current_filter(), 0);
+        });
+    }
+
+    #[test]
+    fn test_active_filter() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new().build();
+            assert_eq!(file_selector.active_filter().is_none(), true);
+
+            let filter = gtk::FileFilter::new();
+            filter.add_mime_type("text/plain");
+            file_selector.add_filter(&filter);
+            assert_eq!(file_selector.active_filter().is_some(), true);
+
+            file_selector.set_current_filter(gtk::INVALID_LIST_POSITION);
+            assert_eq!(file_selector.active_filter().is_none(), true);
+        });
+    }
+
+    #[test]
+    fn test_set_folder() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new()
+                .current_folder(gio::File::for_path("/tmp"))
+                .build();
+
+            assert_eq!(file_selector.set_folder_uri("file:///"), true);
+            assert_eq!(file_selector.current_folder().unwrap().uri(), "file:///");
+
+            assert_eq!(
+                file_selector.set_folder_path(std::path::Path::new("/tmp")),
+                true
+            );
+            assert_eq!(file_selector.current_folder().unwrap().uri(), "file:///tmp");
+
+            assert_eq!(file_selector.set_folder_uri("recent:///"), false);
+            assert_eq!(
+                file_selector.set_folder_path(std::path::Path::new("/does/not/exist")),
+                false
+            );
+            assert_eq!(file_selector.current_folder().unwrap().uri(), "file:///tmp");
+
+            file_selector.set_property("current-folder", None::<gio::File>);
+            assert_eq!(file_selector.current_folder().is_none(), true);
+            assert_eq!(file_selector.model().n_items(), 0);
+        });
+    }
+
+    #[test]
+    fn test_file_activation() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new().build();
+            assert_eq!(file_selector.file_activation(), ActivateAction::Accept);
+
+            let file_selector = FileSelectorBuilder::new()
+                .file_activation(ActivateAction::Select)
+                .build();
+            assert_eq!(file_selector.file_activation(), ActivateAction::Select);
+        });
+    }
+
+    #[test]
+    fn test_choose_folder() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new()
+                .current_folder(gio::File::for_path("/tmp"))
+                .close_on_done(false)
+                .build();
+            // Disabled unless folders are accepted
+            let _ = file_selector.activate_action("file-selector.choose-folder", None);
+            assert_eq!(file_selector.done(), false);
+
+            file_selector.set_accept_folders(true);
+            assert_eq!(
+                file_selector
+                    .activate_action("file-selector.choose-folder", None)
+                    .is_ok(),
+                true
+            );
+            assert_eq!(file_selector.done(), true);
+            assert_eq!(file_selector.selected().unwrap(), vec!["file:///tmp"]);
+        });
+    }
+
+    #[test]
+    fn test_file_dialog() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let dialog = FileDialog::new();
+            assert_eq!(dialog.modal(), true);
+            assert_eq!(dialog.filters().is_none(), true);
+
+            dialog.set_initial_folder(Some(&gio::File::for_path("/tmp")));
+            assert_eq!(dialog.initial_folder().unwrap().uri(), "file:///tmp");
+        });
+    }
+
+    // Runs `dialog` for `action` and hands the shown file selector to `check`
+    fn with_dialog_selector(
+        dialog: &FileDialog,
+        action: gtk::FileChooserAction,
+        check: impl FnOnce(&FileSelector),
+    ) {
+        let result = Rc::new(RefCell::new(None));
+        glib::spawn_future_local(glib::clone!(
+            #[strong]
+            dialog,
+            #[strong]
+            result,
+            async move {
+                let res = dialog.choose(action, None::<&gtk::Window>).await;
+                result.replace(Some(res));
+            }
+        ));
+
+        let ctx = glib::MainContext::default();
+        let mut file_selector = None;
+        for _ in 0..100 {
+            file_selector = gtk::Window::list_toplevels()
+                .into_iter()
+                .filter_map(|w| w.downcast::<FileSelector>().ok())
+                .find(|w| w.title().as_deref() == Some(dialog.title().as_str()));
+            if file_selector.is_some() {
+                break;
+            }
+            ctx.iteration(false);
+        }
+        let file_selector = file_selector.expect("File selector not shown");
+        check(&file_selector);
+
+        // Closing dismisses the dialog
+        file_selector.close();
+        for _ in 0..100 {
+            if result.borrow().is_some() {
+                break;
+            }
+            ctx.iteration(false);
+        }
+        let err = result.take().unwrap().unwrap_err();
+        assert_eq!(err.matches(gtk::DialogError::Dismissed), true);
+    }
+
+    #[test]
+    fn test_file_dialog_actions() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let dialog = FileDialog::new();
+            dialog.set_initial_name("notes.txt");
+
+            dialog.set_title("Open Notes");
+            with_dialog_selector(&dialog, gtk::FileChooserAction::Open, |file_selector| {
+                assert_eq!(file_selector.mode(), FileSelectorMode::OpenFile);
+                assert_eq!(file_selector.directory(), false);
+                assert_eq!(file_selector.accept_label(), "_Open");
+            });
+
+            dialog.set_title("Save Notes");
+            with_dialog_selector(&dialog, gtk::FileChooserAction::Save, |file_selector| {
+                assert_eq!(file_selector.mode(), FileSelectorMode::SaveFile);
+                assert_eq!(file_selector.directory(), false);
+                assert_eq!(file_selector.filename(), "notes.txt");
+                assert_eq!(file_selector.accept_label(), "_Save");
+            });
+
+            dialog.set_title("Select Notes Folder");
+            with_dialog_selector(
+                &dialog,
+                gtk::FileChooserAction::SelectFolder,
+                |file_selector| {
+                    assert_eq!(file_selector.mode(), FileSelectorMode::OpenFile);
+                    assert_eq!(file_selector.directory(), true);
+                    assert_eq!(file_selector.accept_label(), "_Select");
+                },
+            );
+
+            // An explicit label wins
+            dialog.set_title("Attach Notes");
+            dialog.set_accept_label("_Attach");
+            with_dialog_selector(&dialog, gtk::FileChooserAction::Open, |file_selector| {
+                assert_eq!(file_selector.accept_label(), "_Attach");
+            });
+        });
+    }
+
+    #[test]
+    fn test_history() {
+        gtk::test_synced(|| {
+            pfs::init::init();
+
+            let file_selector = FileSelectorBuilder::new()
+                .current_folder(gio::File::for_path("/tmp"))
+                .build();
+            assert_eq!(file_selector.can_go_back(), false);
+            assert_eq!(file_selector.can_go_forward(), false);
+
+            file_selector.set_current_folder(gio::File::for_path("/"));
+            assert_eq!(file_selector.can_go_back(), true);
+            assert_eq!(file_selector.can_go_forward(), false);
+
+            assert_eq!(
+                file_selector
+                    .activate_action("file-selector.back", None)
+                    .is_ok(),
+                true
+            );
+            assert_eq!(file_selector.current_folder().unwrap().uri(), "file:///tmp");
+            assert_eq!(file_selector.can_go_back(), false);
+            assert_eq!(file_selector.can_go_forward(), true);
+        });
     }
 }